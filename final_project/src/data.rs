@@ -0,0 +1,390 @@
+/* THIBAUT STUSSI'S DS210 FINAL PROJECT */
+/* DATA MODULE */
+/* Thibaut Stussi | thibauts@bu.edu */
+
+
+/* DATA MODULE */
+//This module loads the csv into StudentRecords and splits them into train/test groups
+//The graphs (graph.rs) and the DecisionTree (tree.rs) are both made from what this returns
+
+use crate::graph::{self, StudentRecord};
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::io;
+use rand::seq::SliceRandom;
+use rand::Rng; //given feedback from the professor, I am using this for testing
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+//all of the students in the csv
+//a student's id is their row in the loaded data (starting at 1), so ids stay the same no matter how the data is split
+#[derive(Debug, Clone, Default)]
+pub struct StudentData {
+    pub records: Vec<StudentRecord>,
+}
+
+impl StudentData {
+    //gets a student from their id
+    pub fn get(&self, id: usize) -> Option<&StudentRecord> {
+        if id == 0 {
+            return None;
+        }
+        self.records.get(id - 1)
+    }
+
+    //every id in the data, in order
+    pub fn ids(&self) -> Vec<usize> {
+        (1..=self.records.len()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    //copies out the students for a group of ids (ex: the train ids of a Split) to give to the DecisionTree
+    pub fn subset(&self, ids: &[usize]) -> Vec<StudentRecord> {
+        ids.iter().filter_map(|&id| self.get(id).cloned()).collect()
+    }
+}
+
+/* LOADING */
+//what to do with a blank cell in a column
+#[derive(Debug, Clone, PartialEq)]
+#[allow(dead_code)] //main.rs only uses Skip and Keep
+pub enum MissingPolicy {
+    //drop the whole row
+    Skip,
+    //fill in the most common value of the column
+    Mode,
+    //fill in the median of the column (numeric columns only)
+    Median,
+    //fill in this value (it has to be a value the column takes, checked before any row is read)
+    Constant(String),
+    //keep it as the Missing category (categorical columns only)
+    Keep,
+}
+
+//how read_csv handles blank cells (per column) and rows it can't read
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    pub columns: HashMap<String, MissingPolicy>,
+    //used for every column not in columns
+    pub default: MissingPolicy,
+    //true skips (and reports) rows that can't be read, false stops the load like before
+    pub skip_malformed: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions { columns: HashMap::new(), default: MissingPolicy::Skip, skip_malformed: true }
+    }
+}
+
+impl LoadOptions {
+    //sets the policy of one column, ex: LoadOptions::default().with("teacher_quality", MissingPolicy::Keep)
+    pub fn with(mut self, column: &str, policy: MissingPolicy) -> Self {
+        self.columns.insert(column.to_string(), policy);
+        self
+    }
+
+    fn policy(&self, column: &str) -> &MissingPolicy {
+        self.columns.get(column).unwrap_or(&self.default)
+    }
+}
+
+//what happened while loading, counts are per column (only columns that had blanks show up)
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    //rows in the csv and rows that ended up in the StudentData
+    pub rows: usize,
+    pub loaded: usize,
+    //blank cells in each column
+    pub missing: HashMap<String, usize>,
+    //rows dropped, filled in, or kept as Missing because of each column
+    pub skipped: HashMap<String, usize>,
+    pub imputed: HashMap<String, usize>,
+    pub kept: HashMap<String, usize>,
+    //rows that couldn't be read at all (line number and why)
+    pub malformed: Vec<(u64, String)>,
+}
+
+//the columns that can use the Missing category and the ones that can use the median
+const CATEGORY_COLUMNS: [&str; 10] = ["parental_involvement", "access_to_resources", "motivation_level", "family_income",
+    "teacher_quality", "school_type", "peer_influence", "parental_education_level", "distance_from_home", "gender"];
+const NUMERIC_COLUMNS: [&str; 7] = ["hours_studied", "attendance", "sleep_hours", "previous_scores", "tutoring_sessions",
+    "physical_activity", "exam_score"];
+
+//take and heavily edited from my hw9 code but structure is the same
+//only loads the data now, splitting is split() and edges are graph::build_graph()
+//a student's id is their position in the loaded data (so skipped rows don't get an id)
+pub fn read_csv(path: &str, options: &LoadOptions) -> Result<(StudentData, LoadReport), Box<dyn Error>> {
+    //yes headers reader
+    //for some reason I do not need to import use csv::ReaderBuilder;??? eh if it works it works
+    let reader = csv::ReaderBuilder::new().has_headers(true).from_path(path)?;
+    load(reader, options)
+}
+
+//does the actual loading for read_csv, works on any csv reader (tests use a string)
+pub fn load<R: io::Read>(mut reader: csv::Reader<R>, options: &LoadOptions) -> Result<(StudentData, LoadReport), Box<dyn Error>> {
+    let headers = reader.headers()?.clone();
+    let mut report = LoadReport::default();
+
+    //checks the policies make sense before doing anything
+    for column in headers.iter() {
+        match options.policy(column) {
+            MissingPolicy::Keep if !CATEGORY_COLUMNS.contains(&column) =>
+                return Err(format!("{} has no Missing category, use another policy", column).into()),
+            MissingPolicy::Median if !NUMERIC_COLUMNS.contains(&column) =>
+                return Err(format!("{} is not numeric, it has no median", column).into()),
+            //a Constant that isn't a value of the column would make every row it fills in fail
+            MissingPolicy::Constant(x) => match graph::categories(column) {
+                Some(vocab) if !vocab.contains(&x.as_str()) =>
+                    return Err(format!("{} can't be filled in with \"{}\", it takes one of {:?}", column, x, vocab).into()),
+                None if NUMERIC_COLUMNS.contains(&column) && x.parse::<i32>().is_err() =>
+                    return Err(format!("{} is numeric, can't fill it in with \"{}\"", column, x).into()),
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    //first pass reads every row as text so the mode/median can be found before filling anything in
+    let mut rows: Vec<csv::StringRecord> = Vec::new();
+    for result in reader.records() {
+        report.rows += 1;
+        match result {
+            Ok(row) => rows.push(row),
+            Err(e) if options.skip_malformed => {
+                let line = e.position().map(|p| p.line()).unwrap_or(0);
+                report.malformed.push((line, e.to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    //the value to fill in for each column that needs one
+    let mut fill: HashMap<usize, String> = HashMap::new();
+    for (i, column) in headers.iter().enumerate() {
+        let values: Vec<&str> = rows.iter().filter_map(|row| row.get(i)).filter(|x| !is_missing(x)).collect();
+        let value = match options.policy(column) {
+            MissingPolicy::Mode => mode(&values),
+            MissingPolicy::Median => median(&values),
+            MissingPolicy::Constant(x) => Some(x.clone()),
+            //any real category, it's only there so the row can be read (the column is set to Missing after)
+            MissingPolicy::Keep => graph::categories(column).map(|vocab| vocab[0].to_string()),
+            MissingPolicy::Skip => None,
+        };
+        if let Some(value) = value {
+            fill.insert(i, value);
+        }
+    }
+
+    let mut records: Vec<StudentRecord> = Vec::new();
+    for mut row in rows {
+        let mut skip = false;
+        let mut cells: Vec<String> = Vec::new();
+        let mut kept: Vec<String> = Vec::new();
+        for (i, cell) in row.iter().enumerate() {
+            if !is_missing(cell) {
+                cells.push(cell.to_string());
+                continue;
+            }
+            let column = headers.get(i).unwrap_or_default().to_string();
+            *report.missing.entry(column.clone()).or_insert(0) += 1;
+            match (options.policy(&column), fill.get(&i)) {
+                (MissingPolicy::Keep, Some(value)) => {
+                    *report.kept.entry(column.clone()).or_insert(0) += 1;
+                    kept.push(column);
+                    cells.push(value.clone());
+                }
+                (_, Some(value)) => {
+                    *report.imputed.entry(column).or_insert(0) += 1;
+                    cells.push(value.clone());
+                }
+                //Skip (or nothing to fill in with, ex: an empty column)
+                (_, None) => {
+                    *report.skipped.entry(column).or_insert(0) += 1;
+                    skip = true;
+                    cells.push(String::new());
+                }
+            }
+        }
+        if skip {
+            continue;
+        }
+
+        //each line as a StudentRecord (bad values like "Medum" fail here)
+        let position = row.position().cloned();
+        row = csv::StringRecord::from(cells);
+        match row.deserialize::<StudentRecord>(Some(&headers)) {
+            Ok(mut student) => {
+                for column in &kept {
+                    student.set_missing(column);
+                }
+                records.push(student);
+            }
+            Err(e) if options.skip_malformed => {
+                let line = position.map(|p| p.line()).unwrap_or(0);
+                report.malformed.push((line, e.to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    report.loaded = records.len();
+    Ok((StudentData { records }, report))
+}
+
+//blank cells (some versions of the Kaggle data have them)
+fn is_missing(cell: &str) -> bool {
+    cell.trim().is_empty()
+}
+
+//most common value, ties go to the first one alphabetically so it's always the same
+fn mode(values: &[&str]) -> Option<String> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    let max = *counts.values().max()?;
+    counts.into_iter().find(|(_, c)| *c == max).map(|(v, _)| v.to_string())
+}
+
+//middle value of a numeric column, always one of the real values so it still reads as an integer
+fn median(values: &[&str]) -> Option<String> {
+    let mut numbers: Vec<i64> = values.iter().filter_map(|x| x.trim().parse().ok()).collect();
+    if numbers.is_empty() {
+        return None;
+    }
+    numbers.sort();
+    Some(numbers[numbers.len() / 2].to_string())
+}
+
+/* SPLITTING */
+//every random step takes the same rng (made from a seed) so two runs with the same seed give the exact same results
+//make one with this and pass it along to everything that needs randomness
+pub fn seeded_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+//the ids of the students in each group
+#[derive(Debug, Clone, Default)]
+pub struct Split {
+    pub train: Vec<usize>,
+    pub test: Vec<usize>,
+}
+
+/* IMPORTANT */
+//ONLY *percent* of the data is used for training given how dense the graph it, I need to work on this more later
+//this was told to me by Prof. Chator to do, shouldn't affect overall analysis tho
+//IMPORTANT: percent needs to be a value between 0.0-1.0.
+#[allow(dead_code)] //main.rs uses split_stratified now
+pub fn split(data: &StudentData, percent: f64, rng: &mut impl Rng) -> Split {
+    let mut groups = Split::default();
+
+    for id in data.ids() {
+        //each student has a *percent* chance to go to train, otherwise test
+        if rng.gen_bool(percent) {
+            groups.train.push(id);
+        }
+        else {
+            groups.test.push(id);
+        }
+    }
+    groups
+}
+
+//same as split() but gives exactly train_size students to train (the rest go to test)
+#[allow(dead_code)]
+pub fn split_exact(data: &StudentData, train_size: usize, rng: &mut impl Rng) -> Split {
+    let mut ids = data.ids();
+    ids.shuffle(rng);
+    let train_size = train_size.min(ids.len());
+
+    let mut groups = Split { train: ids[..train_size].to_vec(), test: ids[train_size..].to_vec() };
+    //back in csv order like split()
+    groups.train.sort();
+    groups.test.sort();
+    groups
+}
+
+//what to stratify on: any get_attribute names, and exam_score in bins of score_bin points (if given)
+//ex: Strata::new(vec!["learning_disabilities", "school_type"], Some(5))
+#[derive(Debug, Clone, Default)]
+pub struct Strata {
+    pub attributes: Vec<String>,
+    pub score_bin: Option<i32>,
+}
+
+impl Strata {
+    pub fn new(attributes: Vec<&str>, score_bin: Option<i32>) -> Self {
+        Strata { attributes: attributes.into_iter().map(|a| a.to_string()).collect(), score_bin }
+    }
+
+    //the group a student belongs to
+    fn key(&self, student: &StudentRecord) -> Vec<String> {
+        let mut key: Vec<String> = self.attributes.iter().map(|a| student.get_attribute(a).unwrap_or_default()).collect();
+        if let Some(bin) = self.score_bin {
+            key.push((student.exam_score.div_euclid(bin.max(1))).to_string());
+        }
+        key
+    }
+
+    //puts the ids into their groups, groups come out in the same order every time
+    pub fn groups(&self, data: &StudentData, ids: &[usize]) -> Vec<Vec<usize>> {
+        let mut groups: BTreeMap<Vec<String>, Vec<usize>> = BTreeMap::new();
+        for &id in ids {
+            if let Some(student) = data.get(id) {
+                groups.entry(self.key(student)).or_default().push(id);
+            }
+        }
+        groups.into_values().collect()
+    }
+}
+
+//splits every group of the strata on its own so train and test have the same amount of each group
+//ex: if 10% of students have a learning disability, about 10% of both train and test will too
+//IMPORTANT: percent needs to be a value between 0.0-1.0.
+pub fn split_stratified(data: &StudentData, percent: f64, strata: &Strata, rng: &mut impl Rng) -> Split {
+    let mut groups = Split::default();
+
+    for mut group in strata.groups(data, &data.ids()) {
+        group.shuffle(rng);
+        let train_size = (group.len() as f64 * percent).round() as usize;
+        groups.train.extend(&group[..train_size]);
+        groups.test.extend(&group[train_size..]);
+    }
+    groups.train.sort();
+    groups.test.sort();
+    groups
+}
+
+//splits the ids into k folds, each Split has one fold as test and the other k-1 as train
+//with strata, every group is dealt out across the folds on its own (stratified k-fold)
+pub fn k_fold(data: &StudentData, ids: &[usize], k: usize, strata: Option<&Strata>, rng: &mut impl Rng) -> Vec<Split> {
+    let k = k.max(2);
+    let groups = match strata {
+        Some(strata) => strata.groups(data, ids),
+        None => vec![ids.to_vec()],
+    };
+
+    //deals the students out one at a time like cards, carrying on from the last group so the folds stay the same size
+    let mut folds: Vec<Vec<usize>> = vec![Vec::new(); k];
+    let mut next = 0;
+    for mut group in groups {
+        group.shuffle(rng);
+        for id in group {
+            folds[next].push(id);
+            next = (next + 1) % k;
+        }
+    }
+
+    (0..k).map(|i| {
+        let mut train: Vec<usize> = folds.iter().enumerate().filter(|(j, _)| *j != i).flat_map(|(_, f)| f.clone()).collect();
+        let mut test = folds[i].clone();
+        train.sort();
+        test.sort();
+        Split { train, test }
+    }).collect()
+}
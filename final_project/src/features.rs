@@ -0,0 +1,199 @@
+/* THIBAUT STUSSI'S DS210 FINAL PROJECT */
+/* FEATURES MODULE */
+/* Thibaut Stussi | thibauts@bu.edu */
+
+
+/* FEATURES MODULE */
+//This module turns StudentRecords into the rows of numbers the models train on
+//decision_tree() and prediction() used to each have their own copy of the one-hot code, now they both use one FeatureEncoder
+
+use crate::graph::StudentRecord;
+
+use std::error::Error;
+use serde::{Deserialize, Serialize};
+use ndarray::Array2;
+
+//which columns the model gets and how each one is turned into numbers
+//  - categorical: one-hot, one column per category
+//  - ordinal: one column, Low/Medium/High style categories become 0/1/2 (see ordinal_value)
+//  - numeric: one column, the number itself
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeatureSet {
+    pub categorical: Vec<String>,
+    pub ordinal: Vec<String>,
+    pub numeric: Vec<String>,
+}
+
+impl FeatureSet {
+    pub fn new(categorical: Vec<&str>, ordinal: Vec<&str>, numeric: Vec<&str>) -> Self {
+        FeatureSet {
+            categorical: categorical.into_iter().map(|c| c.to_string()).collect(),
+            ordinal: ordinal.into_iter().map(|c| c.to_string()).collect(),
+            numeric: numeric.into_iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    //the original 9 features (same as the old hand written one-hot code)
+    #[allow(dead_code)] //main.rs uses all() now
+    pub fn original() -> Self {
+        FeatureSet::new(vec!["school_type", "family_income", "peer_influence", "motivation_level", "learning_disabilities"],
+            vec![],
+            vec!["hours_studied", "attendance", "previous_scores", "tutoring_sessions"])
+    }
+
+    //all 19 attributes of a StudentRecord (everything but exam_score)
+    //the Low/Medium/High style columns are ordinal, the rest of the categories are one-hot
+    pub fn all() -> Self {
+        FeatureSet::new(vec!["school_type", "gender", "internet_access", "extracurricular_activities", "learning_disabilities"],
+            vec!["parental_involvement", "access_to_resources", "motivation_level", "family_income", "teacher_quality",
+                "peer_influence", "parental_education_level", "distance_from_home"],
+            vec!["hours_studied", "attendance", "sleep_hours", "previous_scores", "tutoring_sessions", "physical_activity"])
+    }
+
+    //true if the column is one of the features
+    #[allow(dead_code)] //only the tests check a FeatureSet for a column right now
+    pub fn contains(&self, column: &str) -> bool {
+        self.categorical.iter().chain(&self.ordinal).chain(&self.numeric).any(|c| c == column)
+    }
+}
+
+//the order of the ordinal categories, Missing (or anything else) is -1.0 so it sits below all of them
+pub fn ordinal_value(value: &str) -> f64 {
+    match value {
+        "Low" | "Negative" | "Near" | "High School" | "No" => 0.0,
+        "Medium" | "Neutral" | "Moderate" | "College" | "Yes" => 1.0,
+        "High" | "Positive" | "Far" | "Postgraduate" => 2.0,
+        _ => -1.0,
+    }
+}
+
+//the categories of an ordinal column in ordinal_value order (0.0, 1.0, 2.0)
+pub fn ordinal_labels(column: &str) -> [&'static str; 3] {
+    match column {
+        "peer_influence" => ["Negative", "Neutral", "Positive"],
+        "distance_from_home" => ["Near", "Moderate", "Far"],
+        "parental_education_level" => ["High School", "College", "Postgraduate"],
+        _ => ["Low", "Medium", "High"],
+    }
+}
+
+//fitted on the training students, it remembers every category it saw for each one-hot column (its vocabulary)
+//the same encoder has to be used to predict so the columns always line up with what the model was trained on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureEncoder {
+    //column name and the categories seen in training (sorted), each category is one column
+    pub categorical: Vec<(String, Vec<String>)>,
+    //column names, one column each
+    pub ordinal: Vec<String>,
+    pub numeric: Vec<String>,
+}
+
+impl FeatureEncoder {
+    //learns the vocabulary of each categorical column from the students
+    pub fn fit(students: &[StudentRecord], features: &FeatureSet) -> Self {
+        let mut encoder = FeatureEncoder::default();
+        for column in &features.categorical {
+            let mut vocab: Vec<String> = students.iter().filter_map(|s| s.get_attribute(column)).collect();
+            vocab.sort();
+            vocab.dedup();
+            encoder.categorical.push((column.clone(), vocab));
+        }
+        encoder.ordinal = features.ordinal.clone();
+        encoder.numeric = features.numeric.clone();
+        encoder
+    }
+
+    //the FeatureSet it was fitted with (to train a new model on the same features)
+    #[allow(dead_code)] //kept for retraining a loaded model, nothing in main.rs does that yet
+    pub fn feature_set(&self) -> FeatureSet {
+        FeatureSet {
+            categorical: self.categorical.iter().map(|(c, _)| c.clone()).collect(),
+            ordinal: self.ordinal.clone(),
+            numeric: self.numeric.clone(),
+        }
+    }
+
+    //amount of columns a student turns into
+    pub fn len(&self) -> usize {
+        self.categorical.iter().map(|(_, vocab)| vocab.len()).sum::<usize>() + self.ordinal.len() + self.numeric.len()
+    }
+
+    //name of every column in the same order as encode(), ex: "school_type=Public" or "hours_studied"
+    pub fn feature_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for (column, vocab) in &self.categorical {
+            for category in vocab {
+                names.push(format!("{}={}", column, category));
+            }
+        }
+        names.extend(self.ordinal.iter().cloned());
+        names.extend(self.numeric.iter().cloned());
+        names
+    }
+
+    //the encoded columns that came from each original column, ex: ("school_type", [0, 1])
+    //a one-hot block has to be treated as one feature (ex: permuted together for feature importance)
+    pub fn feature_groups(&self) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut next = 0;
+        for (column, vocab) in &self.categorical {
+            groups.push((column.clone(), (next..next + vocab.len()).collect()));
+            next += vocab.len();
+        }
+        for column in self.ordinal.iter().chain(&self.numeric) {
+            groups.push((column.clone(), vec![next]));
+            next += 1;
+        }
+        groups
+    }
+
+    //a split "column feature <= threshold" in words, (the left side, the right side)
+    //ex: ("school_type is not Public", "school_type is Public") or ("motivation_level in [Missing, Low]", "motivation_level in [Medium, High]")
+    //Missing is -1.0 for an ordinal column so it always goes left, and is listed there
+    pub fn describe_split(&self, feature: usize, threshold: f64) -> (String, String) {
+        let mut next = 0;
+        for (column, vocab) in &self.categorical {
+            if feature < next + vocab.len() {
+                let category = &vocab[feature - next];
+                return (format!("{} is not {}", column, category), format!("{} is {}", column, category));
+            }
+            next += vocab.len();
+        }
+        if let Some(column) = self.ordinal.get(feature - next) {
+            let labels = ordinal_labels(column);
+            let left: Vec<&str> = (0..3).filter(|i| *i as f64 <= threshold).map(|i| labels[i]).collect();
+            let right: Vec<&str> = (0..3).filter(|i| *i as f64 > threshold).map(|i| labels[i]).collect();
+            //only Missing goes left
+            if left.is_empty() {
+                return (format!("{} is Missing", column), format!("{} in [{}]", column, right.join(", ")));
+            }
+            return (format!("{} in [Missing, {}]", column, left.join(", ")), format!("{} in [{}]", column, right.join(", ")));
+        }
+        let column = self.numeric.get(feature - next - self.ordinal.len()).cloned().unwrap_or(format!("feature {}", feature));
+        (format!("{} <= {}", column, threshold), format!("{} > {}", column, threshold))
+    }
+
+    //one student to a row of numbers
+    //one-hot for the categories (a category not seen in training is all 0.0s), then the ordinal and numeric columns
+    pub fn encode(&self, student: &StudentRecord) -> Vec<f64> {
+        let mut row: Vec<f64> = Vec::with_capacity(self.len());
+        for (column, vocab) in &self.categorical {
+            let value = student.get_attribute(column).unwrap_or_default();
+            row.extend(vocab.iter().map(|category| if *category == value { 1.0 } else { 0.0 }));
+        }
+        for column in &self.ordinal {
+            row.push(ordinal_value(&student.get_attribute(column).unwrap_or_default()));
+        }
+        for column in &self.numeric {
+            let value = student.get_attribute(column).and_then(|x| x.parse::<f64>().ok()).unwrap_or(0.0);
+            row.push(value);
+        }
+        row
+    }
+
+    //every student as one row of an array
+    pub fn encode_all(&self, students: &[StudentRecord]) -> Result<Array2<f64>, Box<dyn Error>> {
+        let rows: Vec<f64> = students.iter().flat_map(|s| self.encode(s)).collect();
+        Ok(Array2::from_shape_vec((students.len(), self.len()), rows)?)
+    }
+}
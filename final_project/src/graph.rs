@@ -4,20 +4,23 @@
 
 
 /* GRAPH MODULE */
-//This module craetes the Graph and StudentRecord structs, loads the data, and runs the graph analysis
-//Referenced in tree.rs for making the DecisionTree
+//This module craetes the Graph and StudentRecord structs, builds the graphs, and runs the graph analysis
+//StudentRecord is referenced in data.rs for loading the data and tree.rs for making the DecisionTree
 
 use std::collections::{BinaryHeap, HashMap, HashSet}; //main thing for the structs
-use serde::Deserialize;
-use std::error::Error;
-use rand::Rng; //given feedback from the professor, I am using this for testing
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
+use crate::data::StudentData;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::cmp::Ordering; //dijkstra's algo
 
 //the following are all for Dijkstra's algorithm, slightly modified
+//distance is an f64 so it works with every EdgeWeight (1/w isn't a whole number)
 #[derive(Debug, Clone, PartialEq)]
 struct Node {
     id: usize,
-    distance: u32,
+    distance: f64,
 }
 
 impl Eq for Node {}
@@ -25,7 +28,7 @@ impl Eq for Node {}
 impl Ord for Node {
     fn cmp(&self, other: &Self) -> Ordering {
         //min heap by distance
-        other.distance.cmp(&self.distance)
+        other.distance.total_cmp(&self.distance)
     }
 }
 
@@ -36,6 +39,246 @@ impl PartialOrd for Node {
 }
 
 
+//the graph with nodes numbered 0..n (in id order) instead of by id, and each edge as (neighbor, length, weight)
+//a Dijkstra from every node (closeness, betweenness) then only looks things up in Vecs
+struct Indexed {
+    ids: Vec<usize>,
+    index: HashMap<usize, usize>,
+    edges: Vec<Vec<(usize, f64, u32)>>,
+}
+
+//what one Dijkstra fills in, made once and cleared for every source (only the nodes the last one reached get cleared)
+struct Search {
+    distances: Vec<f64>,
+    //a node is settled once it is popped, its distance and predecessors can't change after that
+    settled: Vec<bool>,
+    //the settled nodes, closest first
+    order: Vec<usize>,
+    //(index, weight) of every neighbour a node is reached from with its shortest distance, only with keep_predecessors
+    predecessors: Vec<Vec<(usize, u32)>>,
+    prio_q: BinaryHeap<Node>,
+}
+
+impl Search {
+    fn new(n: usize) -> Self {
+        Search { distances: vec![f64::INFINITY; n], settled: vec![false; n], order: Vec::new(), predecessors: vec![Vec::new(); n], prio_q: BinaryHeap::new() }
+    }
+}
+
+impl Indexed {
+    //Dijkstra's algorithm from source, every neighbour that gives the same shortest distance is kept as a predecessor
+    fn dijkstra(&self, source: usize, search: &mut Search, keep_predecessors: bool) {
+        //every node the last search reached got settled, so those are the only ones to clear
+        for &i in &search.order {
+            search.distances[i] = f64::INFINITY;
+            search.settled[i] = false;
+            search.predecessors[i].clear();
+        }
+        search.order.clear();
+        search.distances[source] = 0.0;
+        search.prio_q.push(Node { id: source, distance: 0.0 });
+
+        while let Some(Node { id, distance }) = search.prio_q.pop() {
+            if search.settled[id] || distance > search.distances[id] {
+                continue;
+            }
+            search.settled[id] = true;
+            search.order.push(id);
+            for &(neighbor, length, weight) in &self.edges[id] {
+                //(with a length 0 edge, ex: a weight 0 edge read as Distance, a settled node could otherwise
+                //pick up a later node as a tied predecessor and the predecessors would go in a circle)
+                if search.settled[neighbor] {
+                    continue;
+                }
+                let new_dist = distance + length;
+                let old_dist = search.distances[neighbor];
+                //sums of 1/w are rounded, so ties are checked with a little room
+                let tied = old_dist.is_finite() && (new_dist - old_dist).abs() <= 1e-9 * old_dist.max(1.0);
+                if new_dist < old_dist && !tied {
+                    search.distances[neighbor] = new_dist;
+                    if keep_predecessors {
+                        search.predecessors[neighbor].clear();
+                        search.predecessors[neighbor].push((id, weight));
+                    }
+                    search.prio_q.push(Node { id: neighbor, distance: new_dist });
+                }
+                else if keep_predecessors && tied {
+                    search.predecessors[neighbor].push((id, weight));
+                }
+            }
+        }
+        //sorted so the path picked out of a tie is always the same one (indexes are in id order)
+        if keep_predecessors {
+            for &i in &search.order {
+                search.predecessors[i].sort();
+                search.predecessors[i].dedup();
+            }
+        }
+    }
+}
+
+/* CATEGORIES */
+//each categorical column gets its own enum instead of a String
+//a typo in the csv (ex: "Medum") is now an error when loading instead of silently encoding as nothing
+//Display gives back the exact text from the csv (used by get_attribute)
+//Missing is only used when data::LoadOptions keeps a blank cell as its own category
+//(it's never read from the csv, a cell that says "Missing" is an unknown value like "Medum")
+
+//Low/Medium/High columns (parental involvement, access to resources, motivation, family income, teacher quality)
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Level {
+    #[default]
+    Low,
+    Medium,
+    High,
+    #[serde(skip_deserializing)]
+    Missing,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum SchoolType {
+    #[default]
+    Public,
+    Private,
+    #[serde(skip_deserializing)]
+    Missing,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum PeerInfluence {
+    #[default]
+    Negative,
+    Neutral,
+    Positive,
+    #[serde(skip_deserializing)]
+    Missing,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Distance {
+    #[default]
+    Near,
+    Moderate,
+    Far,
+    #[serde(skip_deserializing)]
+    Missing,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum EducationLevel {
+    #[default]
+    #[serde(rename = "High School")]
+    HighSchool,
+    College,
+    Postgraduate,
+    #[serde(skip_deserializing)]
+    Missing,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Gender {
+    #[default]
+    Male,
+    Female,
+    #[serde(skip_deserializing)]
+    Missing,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Level::Low => write!(f, "Low"),
+            Level::Medium => write!(f, "Medium"),
+            Level::High => write!(f, "High"),
+            Level::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+impl fmt::Display for SchoolType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchoolType::Public => write!(f, "Public"),
+            SchoolType::Private => write!(f, "Private"),
+            SchoolType::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+impl fmt::Display for PeerInfluence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PeerInfluence::Negative => write!(f, "Negative"),
+            PeerInfluence::Neutral => write!(f, "Neutral"),
+            PeerInfluence::Positive => write!(f, "Positive"),
+            PeerInfluence::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Distance::Near => write!(f, "Near"),
+            Distance::Moderate => write!(f, "Moderate"),
+            Distance::Far => write!(f, "Far"),
+            Distance::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+impl fmt::Display for EducationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EducationLevel::HighSchool => write!(f, "High School"),
+            EducationLevel::College => write!(f, "College"),
+            EducationLevel::Postgraduate => write!(f, "Postgraduate"),
+            EducationLevel::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+impl fmt::Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Gender::Male => write!(f, "Male"),
+            Gender::Female => write!(f, "Female"),
+            Gender::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+//the text each category column accepts in the csv, None for the numeric columns
+//Missing isn't in here since it can't be read
+pub fn categories(column: &str) -> Option<&'static [&'static str]> {
+    match column {
+        "parental_involvement" | "access_to_resources" | "motivation_level" | "family_income" | "teacher_quality" =>
+            Some(&["Low", "Medium", "High"]),
+        "school_type" => Some(&["Public", "Private"]),
+        "peer_influence" => Some(&["Negative", "Neutral", "Positive"]),
+        "distance_from_home" => Some(&["Near", "Moderate", "Far"]),
+        "parental_education_level" => Some(&["High School", "College", "Postgraduate"]),
+        "gender" => Some(&["Male", "Female"]),
+        "extracurricular_activities" | "internet_access" | "learning_disabilities" => Some(&["Yes", "No"]),
+        _ => None,
+    }
+}
+
+//Yes/No columns are bools, anything else is an error
+fn yes_no<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    match value.as_str() {
+        "Yes" => Ok(true),
+        "No" => Ok(false),
+        _ => Err(de::Error::custom(format!("expected Yes or No, found \"{}\"", value))),
+    }
+}
+
+//back to the csv text for get_attribute
+fn yes_no_str(value: bool) -> String {
+    if value { "Yes".to_string() } else { "No".to_string() }
+}
+
 //struct for the data (useful for impl)
 //while I only plan on using school type, parental income levels, peer and self motivation, and and learning disabilities
 //having this is helpful as it allows me to store of all of the data but lets me focus only on what I think is important (see above)
@@ -45,54 +288,76 @@ pub struct StudentRecord {
     //most entries are not used (as of now) but are kept because it is easier to load the data
     pub hours_studied: i32,
     pub attendance: i32,
-    pub parental_involvement: String,
-    pub access_to_resources: String,
-    pub extracurricular_activities: String,
+    pub parental_involvement: Level,
+    pub access_to_resources: Level,
+    #[serde(deserialize_with = "yes_no")]
+    pub extracurricular_activities: bool,
     pub sleep_hours: i32,
     pub previous_scores: i32,
-    pub motivation_level: String,
-    pub internet_access: String,
+    pub motivation_level: Level,
+    #[serde(deserialize_with = "yes_no")]
+    pub internet_access: bool,
     pub tutoring_sessions: i32,
-    pub family_income: String,
-    pub teacher_quality: String,
-    pub school_type: String,
-    pub peer_influence: String,
+    pub family_income: Level,
+    pub teacher_quality: Level,
+    pub school_type: SchoolType,
+    pub peer_influence: PeerInfluence,
     pub physical_activity: i32,
-    pub learning_disabilities: String,
-    pub parental_education_level: String,
-    pub distance_from_home: String,
-    pub gender: String,
+    #[serde(deserialize_with = "yes_no")]
+    pub learning_disabilities: bool,
+    pub parental_education_level: EducationLevel,
+    pub distance_from_home: Distance,
+    pub gender: Gender,
     pub exam_score: i32,
 }
 
 impl StudentRecord {
     //helps me get the attribute give a string
     //added all types for redundancy (probably not going to use them all)
+    //categories come back as the same text as the csv
     pub fn get_attribute(&self, a: &str) -> Option<String> {
         match a {
             "hours_studied" => Some(self.hours_studied.to_string()),
             "attendance" => Some(self.attendance.to_string()),
-            "parental_involvement" => Some(self.parental_involvement.clone()),
-            "access_to_resources" => Some(self.access_to_resources.clone()),
-            "extracurricular_activities" => Some(self.extracurricular_activities.clone()),
+            "parental_involvement" => Some(self.parental_involvement.to_string()),
+            "access_to_resources" => Some(self.access_to_resources.to_string()),
+            "extracurricular_activities" => Some(yes_no_str(self.extracurricular_activities)),
             "sleep_hours" => Some(self.sleep_hours.to_string()),
             "previous_scores" => Some(self.previous_scores.to_string()),
-            "motivation_level" => Some(self.motivation_level.clone()),
-            "internet_access" => Some(self.internet_access.clone()),
+            "motivation_level" => Some(self.motivation_level.to_string()),
+            "internet_access" => Some(yes_no_str(self.internet_access)),
             "tutoring_sessions" => Some(self.tutoring_sessions.to_string()),
-            "family_income" => Some(self.family_income.clone()),
-            "teacher_quality" => Some(self.teacher_quality.clone()),
-            "school_type" => Some(self.school_type.clone()),
-            "peer_influence" => Some(self.peer_influence.clone()),
+            "family_income" => Some(self.family_income.to_string()),
+            "teacher_quality" => Some(self.teacher_quality.to_string()),
+            "school_type" => Some(self.school_type.to_string()),
+            "peer_influence" => Some(self.peer_influence.to_string()),
             "physical_activity" => Some(self.physical_activity.to_string()),
-            "learning_disabilities" => Some(self.learning_disabilities.clone()),
-            "parental_education_level" => Some(self.parental_education_level.clone()),
-            "distance_from_home" => Some(self.distance_from_home.clone()),
-            "gender" => Some(self.gender.clone()),
+            "learning_disabilities" => Some(yes_no_str(self.learning_disabilities)),
+            "parental_education_level" => Some(self.parental_education_level.to_string()),
+            "distance_from_home" => Some(self.distance_from_home.to_string()),
+            "gender" => Some(self.gender.to_string()),
             "exam_score" => Some(self.exam_score.to_string()),
             _ => None,
         }
     }
+
+    //puts a category column to Missing (data.rs does this for blank cells it keeps), false if the column has no Missing
+    pub fn set_missing(&mut self, a: &str) -> bool {
+        match a {
+            "parental_involvement" => self.parental_involvement = Level::Missing,
+            "access_to_resources" => self.access_to_resources = Level::Missing,
+            "motivation_level" => self.motivation_level = Level::Missing,
+            "family_income" => self.family_income = Level::Missing,
+            "teacher_quality" => self.teacher_quality = Level::Missing,
+            "school_type" => self.school_type = SchoolType::Missing,
+            "peer_influence" => self.peer_influence = PeerInfluence::Missing,
+            "parental_education_level" => self.parental_education_level = EducationLevel::Missing,
+            "distance_from_home" => self.distance_from_home = Distance::Missing,
+            "gender" => self.gender = Gender::Missing,
+            _ => return false,
+        }
+        true
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -135,8 +400,12 @@ impl Graph {
     //modified print function that prints out a select amount, since all would be too much
     pub fn print(&self, mut lines1: i32, mut lines2: i32) {
         println!("Graph nodes: \n");
+        //sorted by id so it prints the same thing every run
+        let mut ids: Vec<&usize> = self.nodes.keys().collect();
+        ids.sort();
         //prints a selection of student and ids up to the number you give in the call
-        for (id, student) in &self.nodes.clone() {
+        for id in &ids {
+            let student = &self.nodes[*id];
             if lines1 > 0 {
                 println!("Student with id {} has characteristics: {:?}", id, student);
                 lines1 += -1;
@@ -145,7 +414,8 @@ impl Graph {
 
         println!("\nGraph connections: \n");
         //same as above but for edges
-        for (id1, neighbors) in &self.adjacency_list {
+        for id1 in &ids {
+            let neighbors = &self.adjacency_list[*id1];
             if lines2 > 0 {
                 for &(neighbor, weight) in neighbors {
                     println!("Student with id {} is connected to {} with a weight of {}", id1, neighbor, weight);
@@ -175,8 +445,11 @@ impl Graph {
     pub fn clusters(&self, weight: u32, filter: Option<Vec<&str>>) -> Vec<Vec<usize>> {
         let mut visited: HashSet<usize> = HashSet::new();
         let mut parts: Vec<Vec<usize>> = Vec::new();
+        //sorted so the clusters come out in the same order every run
+        let mut ids: Vec<usize> = self.nodes.keys().cloned().collect();
+        ids.sort();
 
-        for &node in self.nodes.keys() {
+        for node in ids {
             //for every node make sure it has not been visited yet
             if !visited.contains(&node) {
                 //stack for processing, and part being the single cluster (will become a vec in parts)
@@ -189,11 +462,11 @@ impl Graph {
 
                         for (neighbor, w) in &self.adjacency_list[&x] {
                             //weight threshold
-                            if w >= &weight && !visited.contains(&neighbor) {
+                            if w >= &weight && !visited.contains(neighbor) {
                                 //checks a filter to see if attributes are the same, pushes if yes
                                 if let Some(attributes) = &filter {
                                     if attributes.iter().all(|a| { 
-                                        self.nodes[&x].get_attribute(a) == self.nodes[&neighbor].get_attribute(a) }) {
+                                        self.nodes[&x].get_attribute(a) == self.nodes[neighbor].get_attribute(a) }) {
                                             stack.push(*neighbor);
                                         }
                                 }
@@ -212,128 +485,661 @@ impl Graph {
 
     //shortest path from id1 to any other node
     //CHANGE TO Dijkstra's
+    //distances with the edge weights taken as they are (EdgeWeight::Distance), u32::MAX if not connected
+    #[allow(dead_code)] //main.rs uses path() and closeness with an EdgeWeight now
     pub fn shortest_path(&self, id1: usize) -> HashMap<usize, u32> {
-        //this is now done using Dijkstra's algorithm
-        let mut distances: HashMap<usize, u32> = HashMap::new();
-        let mut prio_q: BinaryHeap<Node> = BinaryHeap::new();
+        //the predecessors would only slow it down
+        self.dijkstra(id1, EdgeWeight::Distance, self.max_weight(), false).distances.into_iter()
+            .map(|(id, d)| (id, if d.is_finite() { d as u32 } else { u32::MAX }))
+            .collect()
+    }
 
-        //initializes max distance for no connection
-        for &node in self.nodes.keys() {
-            distances.insert(node, u32::MAX);
-        }
+    //Dijkstra's algorithm from id1 that also remembers how each node was reached
+    //every neighbour that gives the same shortest distance is kept as a predecessor, so ties aren't lost
+    //edge_weight says how an edge weight turns into a length (see EdgeWeight)
+    pub fn shortest_paths_from(&self, id1: usize, edge_weight: EdgeWeight) -> ShortestPaths {
+        self.dijkstra(id1, edge_weight, self.max_weight(), true)
+    }
 
-        //start being zero dist
-        distances.insert(id1, 0);
-        prio_q.push(Node { id: id1, distance: 0 });
+    //the biggest edge weight in the graph (EdgeWeight::MaxMinus and NegativeLog need it), 0 with no edges
+    pub fn max_weight(&self) -> u32 {
+        self.adjacency_list.values().flatten().map(|(_, w)| *w).max().unwrap_or(0)
+    }
 
-        while let Some(Node {id, distance }) = prio_q.pop() {
-            if distance > *distances.get(&id).unwrap_or(&u32::MAX) {
-                continue;
-            }
-            //weight accounted for
-            for &(neighbor, weight) in &self.adjacency_list[&id] {
-                let new_dist = distance + weight;
-                    if &new_dist < distances.get(&neighbor).unwrap_or(&u32::MAX) {
-                        distances.insert(neighbor, new_dist);
-                        prio_q.push(Node { id: neighbor, distance: new_dist })
-                    }
+    //max is max_weight(), passed in so closeness doesn't look for it once per node
+    //one Dijkstra with its answer in HashMaps by id, closeness and betweenness run Indexed::dijkstra themselves
+    fn dijkstra(&self, id1: usize, edge_weight: EdgeWeight, max: u32, keep_predecessors: bool) -> ShortestPaths {
+        //initializes infinite distance for no connection, start being zero dist
+        let mut distances: HashMap<usize, f64> = self.nodes.keys().map(|&id| (id, f64::INFINITY)).collect();
+        distances.insert(id1, 0.0);
+        let indexed = self.indexed(edge_weight, max);
+        let Some(&source) = indexed.index.get(&id1) else {
+            return ShortestPaths { source: id1, distances, predecessors: HashMap::new(), order: vec![id1] };
+        };
+
+        let mut search = Search::new(indexed.ids.len());
+        indexed.dijkstra(source, &mut search, keep_predecessors);
+        let mut predecessors: HashMap<usize, Vec<(usize, u32)>> = HashMap::new();
+        for &i in &search.order {
+            distances.insert(indexed.ids[i], search.distances[i]);
+            if !search.predecessors[i].is_empty() {
+                predecessors.insert(indexed.ids[i], search.predecessors[i].iter().map(|&(p, w)| (indexed.ids[p], w)).collect());
             }
         }
-        distances
+        let order = search.order.iter().map(|&i| indexed.ids[i]).collect();
+        ShortestPaths { source: id1, distances, predecessors, order }
+    }
+
+    //the graph with indexes instead of ids and every edge's length worked out once
+    fn indexed(&self, edge_weight: EdgeWeight, max: u32) -> Indexed {
+        let mut ids: Vec<usize> = self.nodes.keys().cloned().collect();
+        ids.sort();
+        let index: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let edges = ids.iter().map(|id| {
+            self.adjacency_list.get(id).into_iter().flatten()
+                .filter_map(|&(neighbor, weight)| index.get(&neighbor).map(|&j| (j, edge_weight.length(weight, max), weight)))
+                .collect()
+        }).collect();
+        Indexed { ids, index, edges }
+    }
+
+    //one shortest path between two students, None if they aren't connected (or one isn't in the graph)
+    //out of tied paths it takes the lowest id predecessor at every step
+    pub fn path(&self, id1: usize, id2: usize, edge_weight: EdgeWeight) -> Option<Path> {
+        self.shortest_paths_from(id1, edge_weight).path_to(id2)
+    }
+
+    //every shortest path between two students (there can be a lot in a dense graph, limit stops after that many)
+    pub fn all_shortest_paths(&self, id1: usize, id2: usize, edge_weight: EdgeWeight, limit: usize) -> Vec<Path> {
+        self.shortest_paths_from(id1, edge_weight).all_paths_to(id2, limit)
     }
     
     //calcualtes closeness centrality for each point
     //does the basic reciprocal sum of shortest distances, nothing complicated
-    pub fn closeness_centrality(&self) -> HashMap<usize, f64> {
-        let mut closeness_cent: HashMap<usize, f64> = HashMap::new();
-
-        for &id in self.nodes.keys() {
-            let shortest_paths = self.shortest_path(id);
-            //since u32::MAX is used for not connected, just remove them (prob won't change much since 1/u32::MAX is v small but still)
-            let sum: u32 = shortest_paths.values().filter_map(|dist| 
-                if *dist == u32::MAX { None } else { Some(*dist) }).sum();
-            //div by 0 error stop
-            if sum > 0 {
-                let closeness = 1.0 / (sum as f64);
-                closeness_cent.insert(id, closeness);
+    //the distances are the path lengths with edge_weight (EdgeWeight::Distance is how it used to work)
+    //a node in a small component can beat a hub this way, see harmonic_centrality and wasserman_faust_closeness
+    //normalized is (n - 1) / sum like the other two
+    #[allow(dead_code)] //main.rs reads it off distance_sums() with the other two
+    pub fn closeness_centrality(&self, edge_weight: EdgeWeight, normalized: bool) -> HashMap<usize, f64> {
+        let n = self.nodes.len();
+        self.distance_sums(edge_weight).into_iter().map(|(id, sums)| (id, sums.closeness(n, normalized))).collect()
+    }
+
+    //sum of 1 / distance to every other node, a node that can't reach someone just gets nothing for them
+    //so small components don't get a boost and nodes with no edges are 0, normalized divides by n - 1
+    #[allow(dead_code)] //main.rs gets every closeness out of one distance_sums()
+    pub fn harmonic_centrality(&self, edge_weight: EdgeWeight, normalized: bool) -> HashMap<usize, f64> {
+        let n = self.nodes.len();
+        self.distance_sums(edge_weight).into_iter().map(|(id, sums)| (id, sums.harmonic(n, normalized))).collect()
+    }
+
+    //Wasserman and Faust's closeness, the closeness inside the node's component (reachable / sum)
+    //times the amount of nodes it reaches, normalized divides by n - 1 (the usual (r / (n - 1)) * (r / sum))
+    #[allow(dead_code)] //same as harmonic_centrality
+    pub fn wasserman_faust_closeness(&self, edge_weight: EdgeWeight, normalized: bool) -> HashMap<usize, f64> {
+        let n = self.nodes.len();
+        self.distance_sums(edge_weight).into_iter().map(|(id, sums)| (id, sums.wasserman_faust(n, normalized))).collect()
+    }
+
+    //one Dijkstra from every node, everything the closeness variants need
+    pub fn distance_sums(&self, edge_weight: EdgeWeight) -> HashMap<usize, DistanceSums> {
+        let indexed = self.indexed(edge_weight, self.max_weight());
+        let mut search = Search::new(indexed.ids.len());
+        (0..indexed.ids.len()).map(|i| {
+            //no predecessors, only the distances are needed
+            indexed.dijkstra(i, &mut search, false);
+            let mut sums = DistanceSums::default();
+            //added up in id order (the indexes are), f64 sums in HashMap order would change in the last digits every run
+            //infinity is used for not connected, just remove them (and the node itself)
+            for (other, &dist) in search.distances.iter().enumerate() {
+                if other == i || dist.is_infinite() {
+                    continue;
+                }
+                sums.reachable += 1;
+                sums.total += dist;
+                //every EdgeWeight keeps the lengths above 0 for calc_weight edges (weight 1 and up)
+                //only a weight 0 edge read as Distance is 0 long, 1 / 0 would take over the whole sum so it's left out
+                if dist > 0.0 {
+                    sums.harmonic += 1.0 / dist;
+                }
+            }
+            (indexed.ids[i], sums)
+        }).collect()
+    }
+}
+
+//the shortest distances from one node added up
+#[derive(Debug, Clone, Default)]
+pub struct DistanceSums {
+    //other nodes it can reach
+    pub reachable: usize,
+    //sum of the distances to them
+    pub total: f64,
+    //sum of 1 / distance to them
+    pub harmonic: f64,
+}
+
+impl DistanceSums {
+    //n is the amount of nodes in the graph
+    //1 / sum (0 for no sum, div by 0 error stop), normalized is (n - 1) / sum
+    pub fn closeness(&self, n: usize, normalized: bool) -> f64 {
+        if self.total <= 0.0 {
+            return 0.0;
+        }
+        if normalized { n.saturating_sub(1) as f64 / self.total } else { 1.0 / self.total }
+    }
+
+    pub fn harmonic(&self, n: usize, normalized: bool) -> f64 {
+        if !normalized {
+            return self.harmonic;
+        }
+        if n > 1 { self.harmonic / (n - 1) as f64 } else { 0.0 }
+    }
+
+    pub fn wasserman_faust(&self, n: usize, normalized: bool) -> f64 {
+        if self.total <= 0.0 {
+            return 0.0;
+        }
+        let r = self.reachable as f64;
+        let value = r * r / self.total;
+        if !normalized {
+            return value;
+        }
+        if n > 1 { value / (n - 1) as f64 } else { 0.0 }
+    }
+}
+
+/* BETWEENNESS CENTRALITY */
+//how often a student is on the shortest paths between two other students, the "bridges" between groups
+impl Graph {
+    //exact betweenness with Brandes' algorithm (one Dijkstra from every node), edge_weight like the path functions
+    //unnormalized counts each pair of students once, normalized divides by the (n-1)(n-2)/2 pairs a node could be between
+    #[allow(dead_code)] //too slow for train_graph, main.rs uses the sampled one
+    pub fn betweenness_centrality(&self, edge_weight: EdgeWeight, normalized: bool) -> HashMap<usize, f64> {
+        let mut ids: Vec<usize> = self.nodes.keys().cloned().collect();
+        ids.sort();
+        self.brandes(&ids, edge_weight, normalized)
+    }
+
+    //the same but only from k random pivot nodes, scaled up by n / k so it estimates the exact values
+    //k >= the amount of nodes gives the exact betweenness
+    pub fn approximate_betweenness(&self, edge_weight: EdgeWeight, k: usize, normalized: bool, rng: &mut impl Rng) -> HashMap<usize, f64> {
+        let mut ids: Vec<usize> = self.nodes.keys().cloned().collect();
+        ids.sort();
+        let mut pivots: Vec<usize> = ids.choose_multiple(rng, k.min(ids.len())).cloned().collect();
+        pivots.sort();
+        self.brandes(&pivots, edge_weight, normalized)
+    }
+
+    //adds up the dependencies from every source, each shortest path is counted from both of its ends so it's halved at the end
+    fn brandes(&self, sources: &[usize], edge_weight: EdgeWeight, normalized: bool) -> HashMap<usize, f64> {
+        let n = self.nodes.len();
+        let indexed = self.indexed(edge_weight, self.max_weight());
+        let mut search = Search::new(n);
+        let mut betweenness = vec![0.0; n];
+        let mut sigma = vec![0.0; n];
+        let mut delta = vec![0.0; n];
+
+        for source in sources.iter().filter_map(|id| indexed.index.get(id)) {
+            indexed.dijkstra(*source, &mut search, true);
+            //amount of shortest paths from the source to each node (f64, it gets huge in a dense graph)
+            //dijkstra only gives a node predecessors that were settled before it, so they are always earlier in order
+            for &i in &search.order {
+                sigma[i] = if i == *source { 1.0 } else { search.predecessors[i].iter().map(|(p, _)| sigma[*p]).sum() };
+                delta[i] = 0.0;
+            }
+            //farthest first, each node passes its share of paths back to its predecessors
+            for &i in search.order.iter().rev() {
+                let d = delta[i];
+                for (p, _) in &search.predecessors[i] {
+                    delta[*p] += sigma[*p] / sigma[i] * (1.0 + d);
+                }
+                if i != *source {
+                    betweenness[i] += d;
+                }
+            }
+        }
+
+        //n / k for the sampled version, 1/2 for the paths counted from both ends
+        let mut scale = if sources.is_empty() { 0.0 } else { n as f64 / sources.len() as f64 } / 2.0;
+        if normalized {
+            scale = if n > 2 { scale * 2.0 / ((n - 1) * (n - 2)) as f64 } else { 0.0 };
+        }
+        indexed.ids.into_iter().zip(betweenness).map(|(id, value)| (id, value * scale)).collect()
+    }
+}
+
+/* SPECTRAL CENTRALITY */
+//degree centrality is almost the same for everyone in a graph this dense, these count how strong the edges are
+//and who they lead to, the weights are used as they are (a stronger similarity passes on more, no EdgeWeight here)
+
+//the scores of an iterative centrality and if they settled before max_iterations
+#[derive(Debug, Clone, Default)]
+pub struct Ranking {
+    pub scores: HashMap<usize, f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PageRankParams {
+    //chance of following an edge instead of jumping
+    pub damping: f64,
+    //where a jump lands (id -> weight, normalized to sum to 1), None is every node equally
+    pub personalization: Option<HashMap<usize, f64>>,
+    //stops once the scores moved less than this in total (sum of the changes)
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for PageRankParams {
+    fn default() -> Self {
+        PageRankParams { damping: 0.85, personalization: None, tolerance: 1e-6, max_iterations: 100 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KatzParams {
+    //how much a neighbour's score counts, has to be below 1 / the largest eigenvalue or it never settles
+    //None uses 0.5 / the largest eigenvalue (found like eigenvector_centrality)
+    pub alpha: Option<f64>,
+    //what every node gets on its own
+    pub beta: f64,
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for KatzParams {
+    fn default() -> Self {
+        KatzParams { alpha: None, beta: 1.0, tolerance: 1e-9, max_iterations: 1000 }
+    }
+}
+
+impl Graph {
+    //sorted ids and each node's edges as (index, weight), so the iterations below are plain Vec loops in the same order every run
+    fn weighted_edges(&self) -> (Vec<usize>, Vec<Vec<(usize, f64)>>) {
+        let mut ids: Vec<usize> = self.nodes.keys().cloned().collect();
+        ids.sort();
+        let index: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let edges = ids.iter().map(|id| {
+            self.adjacency_list.get(id).into_iter().flatten().filter_map(|(n, w)| index.get(n).map(|&j| (j, *w as f64))).collect()
+        }).collect();
+        (ids, edges)
+    }
+
+    //weighted PageRank, a random walk follows an edge with a chance proportional to its weight
+    //(or jumps, to a node picked by the personalization) and the score is how often it is on each student
+    //students with no edges jump every time, the scores sum to 1
+    pub fn pagerank(&self, params: &PageRankParams) -> Ranking {
+        let (ids, edges) = self.weighted_edges();
+        let n = ids.len();
+        if n == 0 {
+            return Ranking { converged: true, ..Default::default() };
+        }
+        let mut jump: Vec<f64> = match &params.personalization {
+            Some(weights) => ids.iter().map(|id| weights.get(id).cloned().unwrap_or(0.0).max(0.0)).collect(),
+            None => vec![1.0; n],
+        };
+        let total: f64 = jump.iter().sum();
+        //a personalization that is all 0s (or only has ids not in the graph) falls back to every node
+        jump = if total > 0.0 { jump.iter().map(|j| j / total).collect() } else { vec![1.0 / n as f64; n] };
+        let strength: Vec<f64> = edges.iter().map(|list| list.iter().map(|(_, w)| w).sum()).collect();
+
+        let mut ranking = Ranking::default();
+        let mut scores = jump.clone();
+        while ranking.iterations < params.max_iterations {
+            ranking.iterations += 1;
+            let dangling: f64 = (0..n).filter(|&i| strength[i] == 0.0).map(|i| scores[i]).sum();
+            let mut next: Vec<f64> = jump.iter().map(|j| (1.0 - params.damping + params.damping * dangling) * j).collect();
+            for i in (0..n).filter(|&i| strength[i] > 0.0) {
+                for (j, w) in &edges[i] {
+                    next[*j] += params.damping * scores[i] * w / strength[i];
+                }
             }
-            else {
-                closeness_cent.insert(id, 0.0);
+            let change: f64 = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).sum();
+            scores = next;
+            if change < params.tolerance {
+                ranking.converged = true;
+                break;
             }
         }
-        closeness_cent
-    }
-}
-
-//take and heavily edited from my hw9 code but structure is the same
-
-/* IMPORTANT */
-//ONLY *percent* of the data is used given how dense the graph it, I need to work on this more later
-//this was told to me by Prof. Chator to do, shouldn't affect overall analysis tho
-
-/* UPDATE */
-//now takes in two graphs, a test and train graph
-//you can determine how much of the overall data goes into the first (train) and second (test) graph with a new parameter
-//IMPORTANT: percent needs to be a value between 0.0-1.0.
-pub fn read_csv(path: &str, graph1: &mut Graph, graph2: &mut Graph, percent: f64) -> Result<(), Box<dyn Error>> {
-    //yes headers reader
-    //for some reason I do not need to import use csv::ReaderBuilder;??? eh if it works it works
-    let mut reader = csv::ReaderBuilder::new().has_headers(true).from_path(path)?;
-    let mut id_count = 1; //id number that we use, go 1 at a time
-    let mut rng = rand::thread_rng();
-
-    //over reach items in the csv
-    for result in reader.deserialize() {
-        //each line as a StudentRecord
-        let student: StudentRecord = result?;
-        //add each line to the graph as its own node (no edges)
-        //20% chance to add since it is hard to run all these commands on such a large graph
-        if rng.gen_bool(percent) {
-            graph1.add_student(student, id_count);
-        }
-        else {
-            graph2.add_student(student, id_count);
-        }
-        //increment id counter
-        id_count += 1;
-    }
-
-    //runs it over the first graph
-    let ids1: Vec<usize> = graph1.nodes.keys().cloned().collect();
-    for i in 0..ids1.len() {
-        //iterates over every student-student connection
-        //do i, i+1 since for connection 20, you've4 already checked 1-19 so no need to repeat
-        //slow for the first half, second half a lot faster
-        for j in (i + 1)..ids1.len() {
-            //get their ndoe
-            let student1 = &graph1.nodes[&ids1[i]];
-            let student2 = &graph1.nodes[&ids1[j]];
-            let weight: u32 = calc_weight(student1, student2);
-            //if there is a connection (weight > 0) add an edge between both ids
-            if weight > 0 {
-                graph1.add_edge(ids1[i], ids1[j], weight);
+        ranking.scores = ids.into_iter().zip(scores).collect();
+        ranking
+    }
+
+    //eigenvector centrality, a student is central if their (strong) neighbours are central
+    //power iteration on A + I (the + I stops it from flipping back and forth forever on graphs like a 2 node one),
+    //scaled to length 1 each iteration, stops once no score moved more than tolerance
+    pub fn eigenvector_centrality(&self, tolerance: f64, max_iterations: usize) -> Ranking {
+        let (ids, edges) = self.weighted_edges();
+        let (scores, iterations, converged, _) = power_iteration(&edges, tolerance, max_iterations);
+        Ranking { scores: ids.into_iter().zip(scores).collect(), iterations, converged }
+    }
+
+    //Katz centrality, x = alpha * A x + beta, every walk to a student counts but longer ones count less (alpha^length)
+    //scaled to length 1 at the end like eigenvector_centrality
+    pub fn katz_centrality(&self, params: &KatzParams) -> Ranking {
+        let (ids, edges) = self.weighted_edges();
+        let alpha = params.alpha.unwrap_or_else(|| {
+            let (_, _, _, eigenvalue) = power_iteration(&edges, params.tolerance, params.max_iterations);
+            if eigenvalue > 0.0 { 0.5 / eigenvalue } else { 0.0 }
+        });
+
+        let mut ranking = Ranking::default();
+        let mut scores = vec![0.0; ids.len()];
+        while ranking.iterations < params.max_iterations {
+            ranking.iterations += 1;
+            let next: Vec<f64> = edges.iter().map(|list| alpha * list.iter().map(|(j, w)| w * scores[*j]).sum::<f64>() + params.beta).collect();
+            let change = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            scores = next;
+            //relative so it doesn't depend on how big beta is
+            if change < params.tolerance * scores.iter().cloned().fold(1.0, f64::max) {
+                ranking.converged = true;
+                break;
             }
         }
+        let norm = scores.iter().map(|x| x * x).sum::<f64>().sqrt();
+        ranking.scores = ids.into_iter().zip(scores.into_iter().map(|x| if norm > 0.0 { x / norm } else { x })).collect();
+        ranking
     }
-    
-    //runs it over the second graph too (repeat code)
-    let ids2: Vec<usize> = graph2.nodes.keys().cloned().collect();
-    for i in 0..ids2.len() {
-        //iterates over every student-student connection
-        //do i, i+1 since for connection 20, you've4 already checked 1-19 so no need to repeat
-        //slow for the first half, second half a lot faster
-        for j in (i + 1)..ids2.len() {
-            //get their ndoe
-            let student1 = &graph2.nodes[&ids2[i]];
-            let student2 = &graph2.nodes[&ids2[j]];
-            let weight: u32 = calc_weight(student1, student2);
-            //if there is a connection (weight > 0) add an edge between both ids
-            if weight > 0 {
-                graph2.add_edge(ids2[i], ids2[j], weight);
+}
+
+//power iteration for the largest eigenvector of the weighted adjacency matrix (see eigenvector_centrality)
+//gives (scores, iterations, converged, largest eigenvalue of A)
+fn power_iteration(edges: &[Vec<(usize, f64)>], tolerance: f64, max_iterations: usize) -> (Vec<f64>, usize, bool, f64) {
+    let n = edges.len();
+    if n == 0 {
+        return (Vec::new(), 0, true, 0.0);
+    }
+    let mut scores = vec![1.0 / (n as f64).sqrt(); n];
+    let mut eigenvalue = 0.0;
+    for iteration in 1..=max_iterations {
+        let mut next: Vec<f64> = (0..n).map(|i| scores[i] + edges[i].iter().map(|(j, w)| w * scores[*j]).sum::<f64>()).collect();
+        let norm = next.iter().map(|x| x * x).sum::<f64>().sqrt();
+        //scores has length 1, so the length of (A + I) scores is the eigenvalue of A + I once it settles
+        eigenvalue = norm - 1.0;
+        if norm == 0.0 {
+            return (next, iteration, true, 0.0);
+        }
+        next.iter_mut().for_each(|x| *x /= norm);
+        let change = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        scores = next;
+        if change < tolerance {
+            return (scores, iteration, true, eigenvalue);
+        }
+    }
+    (scores, max_iterations, false, eigenvalue)
+}
+
+/* SHORTEST PATHS */
+//how the path functions read an edge weight
+//calc_weight style weights are similarities (5 is more alike than 1), but Dijkstra wants short edges between close students
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeWeight {
+    //the weight is already a distance (how the graph was read before, so a weight 5 edge is "far")
+    Distance,
+    //1 / w
+    Inverse,
+    //max + 1 - w, max being the biggest weight in the graph (the most similar students are 1 apart)
+    //the + 1 keeps every length above 0, or the closest students would count as not there in harmonic closeness
+    MaxMinus,
+    //-ln(w / (max + 1)), short for the most similar students but longer and longer as w gets close to 0
+    //(max + 1 for the same reason, -ln(max / max) would be 0)
+    NegativeLog,
+}
+
+impl EdgeWeight {
+    //the length of an edge of weight w, max is Graph::max_weight()
+    pub fn length(&self, w: u32, max: u32) -> f64 {
+        let (w, max) = (w as f64, max as f64);
+        match self {
+            EdgeWeight::Distance => w,
+            EdgeWeight::Inverse => 1.0 / w,
+            EdgeWeight::MaxMinus => max + 1.0 - w,
+            EdgeWeight::NegativeLog => -(w / (max + 1.0)).ln(),
+        }
+    }
+}
+
+//a chain of students, nodes[0] is the start and weights[i] is the edge between nodes[i] and nodes[i + 1]
+//weights are the edge weights of the graph, distance is the length of the path with the EdgeWeight it was found with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub nodes: Vec<usize>,
+    pub weights: Vec<u32>,
+    pub distance: f64,
+}
+
+//what Dijkstra's algorithm found from one source, the distances plus every (predecessor, edge weight) on a shortest path
+#[derive(Debug, Clone)]
+pub struct ShortestPaths {
+    pub source: usize,
+    //f64::INFINITY means not connected
+    pub distances: HashMap<usize, f64>,
+    pub predecessors: HashMap<usize, Vec<(usize, u32)>>,
+    //the reached nodes in the order Dijkstra settled them (closest first)
+    #[allow(dead_code)] //betweenness uses the Search's order now, only handy when looking at one source
+    pub order: Vec<usize>,
+}
+
+impl ShortestPaths {
+    //follows the first predecessor back from the target to the source
+    pub fn path_to(&self, target: usize) -> Option<Path> {
+        let distance = *self.distances.get(&target)?;
+        if distance.is_infinite() {
+            return None;
+        }
+        let mut nodes = vec![target];
+        let mut weights = Vec::new();
+        let mut current = target;
+        while current != self.source {
+            let &(previous, weight) = self.predecessors.get(&current)?.first()?;
+            nodes.push(previous);
+            weights.push(weight);
+            current = previous;
+        }
+        nodes.reverse();
+        weights.reverse();
+        Some(Path { nodes, weights, distance })
+    }
+
+    //every path through the predecessors from the source to the target, at most limit of them
+    pub fn all_paths_to(&self, target: usize, limit: usize) -> Vec<Path> {
+        let mut paths: Vec<Path> = Vec::new();
+        let distance = match self.distances.get(&target) {
+            Some(&d) if d.is_finite() => d,
+            _ => return paths,
+        };
+        //goes backwards from the target, nodes and weights are reversed until a path reaches the source
+        let mut nodes = vec![target];
+        let mut weights = Vec::new();
+        self.walk_back(&mut nodes, &mut weights, distance, limit, &mut paths);
+        paths
+    }
+
+    fn walk_back(&self, nodes: &mut Vec<usize>, weights: &mut Vec<u32>, distance: f64, limit: usize, paths: &mut Vec<Path>) {
+        if paths.len() >= limit {
+            return;
+        }
+        let current = nodes[nodes.len() - 1];
+        if current == self.source {
+            let mut path = Path { nodes: nodes.clone(), weights: weights.clone(), distance };
+            path.nodes.reverse();
+            path.weights.reverse();
+            paths.push(path);
+            return;
+        }
+        for &(previous, weight) in self.predecessors.get(&current).into_iter().flatten() {
+            //only possible with 0 weight edges, but it would never end
+            if nodes.contains(&previous) {
+                continue;
+            }
+            nodes.push(previous);
+            weights.push(weight);
+            self.walk_back(nodes, weights, distance, limit, paths);
+            nodes.pop();
+            weights.pop();
+        }
+    }
+}
+
+/* BUILDING GRAPHS */
+//read_csv used to load the data, split it and build the edges all at once
+//now loading/splitting is in data.rs and this just builds a graph out of any group of students
+//ids are the ids from the StudentData (so the same student has the same id in every graph)
+pub fn build_graph(data: &StudentData, ids: &[usize], similarity: &dyn SimilarityFn) -> Graph {
+    let mut graph = Graph::new();
+    for &id in ids {
+        //add each student to the graph as its own node (no edges)
+        if let Some(student) = data.get(id) {
+            graph.add_student(student.clone(), id);
+        }
+    }
+    build_edges(&mut graph, similarity);
+    graph
+}
+
+//builds every edge of a graph whose nodes are already added
+//if the similarity has a signature, students are put into buckets by it
+//two students in the same pair of buckets always get the same weight, so the weight is found once per pair of buckets instead of once per pair of students
+//otherwise (ex: NumericSimilarity) every pair of students is compared like the original loop
+//both ways give the same edges and weights
+pub fn build_edges(graph: &mut Graph, similarity: &dyn SimilarityFn) {
+    //sorted ids so that the buckets (and the edge order) are the same every run
+    let mut ids: Vec<usize> = graph.nodes.keys().cloned().collect();
+    ids.sort();
+
+    let signatures: Option<Vec<Vec<String>>> = ids.iter().map(|id| similarity.signature(&graph.nodes[id])).collect();
+    let Some(signatures) = signatures else {
+        //no signature, check every student-student connection
+        for i in 0..ids.len() {
+            for j in (i + 1)..ids.len() {
+                let weight = similarity.weight(&graph.nodes[&ids[i]], &graph.nodes[&ids[j]]);
+                //if there is a connection (weight > 0) add an edge between both ids
+                if weight > 0 {
+                    graph.add_edge(ids[i], ids[j], weight);
+                }
+            }
+        }
+        return;
+    };
+
+    let mut buckets: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+    let mut order: Vec<Vec<String>> = Vec::new();
+    for (id, key) in ids.into_iter().zip(signatures) {
+        if !buckets.contains_key(&key) {
+            order.push(key.clone());
+        }
+        buckets.entry(key).or_default().push(id);
+    }
+
+    for a in 0..order.len() {
+        for b in a..order.len() {
+            let bucket1 = &buckets[&order[a]];
+            let bucket2 = &buckets[&order[b]];
+            //any member of the bucket works, they all have the same signature
+            let weight = similarity.weight(&graph.nodes[&bucket1[0]], &graph.nodes[&bucket2[0]]);
+            if weight == 0 {
+                continue;
+            }
+            for (i, &id1) in bucket1.iter().enumerate() {
+                //inside the same bucket only do each pair once (same idea as the i, i+1 loop this replaced)
+                let start = if a == b { i + 1 } else { 0 };
+                for &id2 in &bucket2[start..] {
+                    graph.add_edge(id1, id2, weight);
+                }
+            }
+        }
+    }
+}
+
+/* SIMILARITY */
+//how "similar" two students are, used as the edge weight (0 means no edge)
+//lets the graph be built with different ideas of similar students without editing build_edges
+pub trait SimilarityFn {
+    fn weight(&self, student1: &StudentRecord, student2: &StudentRecord) -> u32;
+
+    //the attributes the weight depends on, two students with the same signature must be identical to weight()
+    //build_edges uses it to bucket students, None means it has to compare every pair
+    fn signature(&self, _student: &StudentRecord) -> Option<Vec<String>> {
+        None
+    }
+}
+
+//the original rule, school type, family income, motivation, peer influence and both having a learning disability
+pub struct DefaultSimilarity;
+
+impl SimilarityFn for DefaultSimilarity {
+    fn weight(&self, student1: &StudentRecord, student2: &StudentRecord) -> u32 {
+        calc_weight(student1, student2)
+    }
+
+    fn signature(&self, student: &StudentRecord) -> Option<Vec<String>> {
+        Some(vec![student.school_type.to_string(), student.family_income.to_string(), student.motivation_level.to_string(),
+            student.peer_influence.to_string(), yes_no_str(student.learning_disabilities)])
+    }
+}
+
+//adds the given weight for every attribute (any get_attribute name) that is the same for both students
+//ex: vec![("school_type", 2), ("gender", 1)]
+#[allow(dead_code)] //not used in main.rs, the other options for building a graph
+pub struct AttributeSimilarity {
+    pub attributes: Vec<(String, u32)>,
+}
+
+#[allow(dead_code)]
+impl AttributeSimilarity {
+    pub fn new(attributes: Vec<(&str, u32)>) -> Self {
+        AttributeSimilarity { attributes: attributes.into_iter().map(|(a, w)| (a.to_string(), w)).collect() }
+    }
+}
+
+impl SimilarityFn for AttributeSimilarity {
+    fn weight(&self, student1: &StudentRecord, student2: &StudentRecord) -> u32 {
+        let mut weight: u32 = 0;
+        for (a, w) in &self.attributes {
+            let value = student1.get_attribute(a);
+            if value.is_some() && value == student2.get_attribute(a) {
+                weight += w;
+            }
+        }
+        weight
+    }
+
+    fn signature(&self, student: &StudentRecord) -> Option<Vec<String>> {
+        Some(self.attributes.iter().map(|(a, _)| student.get_attribute(a).unwrap_or_default()).collect())
+    }
+}
+
+//adds 1 for every numeric attribute where the students are within the tolerance of each other
+//ex: vec![("hours_studied", 2.0), ("attendance", 5.0), ("previous_scores", 5.0)]
+//no signature since "close" is not the same as "equal", so this one compares every pair
+#[allow(dead_code)]
+pub struct NumericSimilarity {
+    pub attributes: Vec<(String, f64)>,
+}
+
+#[allow(dead_code)]
+impl NumericSimilarity {
+    pub fn new(attributes: Vec<(&str, f64)>) -> Self {
+        NumericSimilarity { attributes: attributes.into_iter().map(|(a, t)| (a.to_string(), t)).collect() }
+    }
+}
+
+impl SimilarityFn for NumericSimilarity {
+    fn weight(&self, student1: &StudentRecord, student2: &StudentRecord) -> u32 {
+        let mut weight: u32 = 0;
+        for (a, tolerance) in &self.attributes {
+            let value1 = student1.get_attribute(a).and_then(|v| v.parse::<f64>().ok());
+            let value2 = student2.get_attribute(a).and_then(|v| v.parse::<f64>().ok());
+            if let (Some(x), Some(y)) = (value1, value2) {
+                if (x - y).abs() <= *tolerance {
+                    weight += 1;
+                }
             }
         }
+        weight
     }
-    Ok(())
 }
 
 //calculates the weight of the connection between students
@@ -346,6 +1152,6 @@ pub fn calc_weight(student1: &StudentRecord, student2: &StudentRecord) -> u32 {
     if student1.motivation_level == student2.motivation_level { weight += 1; }
     if student1.peer_influence == student2.peer_influence { weight += 1; }
     //only testing in this case for having a disability
-    if student1.learning_disabilities == "Yes" && student2.learning_disabilities == "Yes" { weight += 1; }
+    if student1.learning_disabilities && student2.learning_disabilities { weight += 1; }
     weight
 }
\ No newline at end of file
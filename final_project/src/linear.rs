@@ -0,0 +1,231 @@
+/* THIBAUT STUSSI'S DS210 FINAL PROJECT */
+/* LINEAR MODULE */
+/* Thibaut Stussi | thibauts@bu.edu */
+
+
+/* LINEAR MODULE */
+//This module fits linear models (ordinary least squares, ridge and lasso) on the same encoded students as the trees
+//the tree says which features matter, a coefficient says by how many points and in which direction
+//goes through tree::Predictor so prediction(), feature_importance() and cross validation work the same as for the tree
+
+use crate::graph::StudentRecord;
+use crate::features::{FeatureEncoder, FeatureSet};
+use crate::tree::{self, Predictor};
+
+use std::error::Error;
+use ndarray::{Array1, Array2, Axis};
+use statrs::distribution::{ContinuousCDF, StudentsT};
+use serde::{Deserialize, Deserializer, Serialize};
+
+//the penalty on the size of the coefficients (never on the intercept)
+//both are added to the mean squared error / 2, so the same lambda is the same amount of shrinking for either one
+//and both are on the standardized coefficients (b * the std of its column), so a column's units don't change its penalty
+//the coefficients still come out in the columns' own units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Penalty {
+    //ordinary least squares
+    None,
+    //adds lambda / 2 * sum(b^2), pulls every coefficient towards 0
+    Ridge(f64),
+    //adds lambda * sum(|b|), can put coefficients at exactly 0
+    Lasso(f64),
+}
+
+//one row of the coefficient report
+//lasso has no usual standard errors so everything after estimate is NaN for it
+//(JSON has no NaN, serde_json saves it as null and nan_or_f64 reads the null back as NaN)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coefficient {
+    pub name: String,
+    pub estimate: f64,
+    #[serde(deserialize_with = "nan_or_f64")]
+    pub std_error: f64,
+    #[serde(deserialize_with = "nan_or_f64")]
+    pub t: f64,
+    //two sided, against the coefficient being 0
+    #[serde(deserialize_with = "nan_or_f64")]
+    pub p_value: f64,
+    //95% confidence interval
+    #[serde(deserialize_with = "nan_or_f64")]
+    pub ci_low: f64,
+    #[serde(deserialize_with = "nan_or_f64")]
+    pub ci_high: f64,
+}
+
+fn nan_or_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinearModel {
+    pub intercept: f64,
+    //one per kept column (see columns)
+    pub coefficients: Vec<f64>,
+    //the encoded columns the model uses, the first category of each one-hot block is left out
+    //(it would always be 1 - the others, so with the intercept the least squares would have no single answer)
+    //the coefficients of the other categories are compared to that left out one
+    pub columns: Vec<usize>,
+    pub encoder: FeatureEncoder,
+    //intercept first, then one per coefficient
+    pub report: Vec<Coefficient>,
+}
+
+impl Predictor for LinearModel {
+    fn encoder(&self) -> &FeatureEncoder {
+        &self.encoder
+    }
+
+    fn predict_row(&self, row: &[f64]) -> f64 {
+        self.intercept + self.columns.iter().zip(&self.coefficients).map(|(&c, b)| row[c] * b).sum::<f64>()
+    }
+}
+
+//fits a linear model on the students, features picks the columns like for decision_tree()
+pub fn linear_regression(students: &[StudentRecord], features: &FeatureSet, penalty: Penalty) -> Result<LinearModel, Box<dyn Error>> {
+    let (encoder, records, targets) = tree::encode_training(students, features)?;
+    //drops the first column of every one-hot block (the reference category)
+    let columns: Vec<usize> = encoder.feature_groups().iter()
+        .enumerate()
+        .flat_map(|(g, (_, cols))| if g < encoder.categorical.len() { cols[1..].to_vec() } else { cols.clone() })
+        .collect();
+    let names = encoder.feature_names();
+
+    //design matrix with a column of 1s for the intercept
+    let (n, p) = (records.nrows(), columns.len() + 1);
+    let mut x = Array2::<f64>::ones((n, p));
+    for (j, &c) in columns.iter().enumerate() {
+        x.column_mut(j + 1).assign(&records.column(c));
+    }
+    let y = Array1::from(targets);
+
+    let ridge = ridge_diagonal(&x, if let Penalty::Ridge(lambda) = penalty { lambda } else { 0.0 });
+    let beta = match penalty {
+        Penalty::None | Penalty::Ridge(_) => least_squares(&x, &y, &ridge)?,
+        Penalty::Lasso(lambda) => lasso(&x, &y, lambda, 10000, 1e-8),
+    };
+
+    let mut report_names = vec!["(intercept)".to_string()];
+    report_names.extend(columns.iter().map(|&c| names[c].clone()));
+    let report = match penalty {
+        Penalty::Lasso(_) => report_names.into_iter().zip(beta.iter()).map(|(name, &estimate)| Coefficient {
+            name, estimate, std_error: f64::NAN, t: f64::NAN, p_value: f64::NAN, ci_low: f64::NAN, ci_high: f64::NAN,
+        }).collect(),
+        Penalty::None | Penalty::Ridge(_) => inference(&x, &y, &beta, &ridge, report_names)?,
+    };
+
+    Ok(LinearModel { intercept: beta[0], coefficients: beta.iter().skip(1).cloned().collect(), columns, encoder, report })
+}
+
+//what ridge adds to the diagonal of X'X: (1/2n) |y - Xb|^2 + lambda/2 sum((std_j b_j)^2) is solved by
+//(X'X + D) b = X'y with D_jj = n lambda std_j^2, 0 for the intercept (std 0 columns are penalized like std 1 ones)
+fn ridge_diagonal(x: &Array2<f64>, lambda: f64) -> Vec<f64> {
+    let n = x.nrows() as f64;
+    let stds = x.std_axis(Axis(0), 0.0);
+    (0..x.ncols()).map(|j| if j == 0 { 0.0 } else { n * lambda * if stds[j] > 0.0 { stds[j].powi(2) } else { 1.0 } }).collect()
+}
+
+//X'X with the ridge diagonal added
+fn gram(x: &Array2<f64>, diagonal: &[f64]) -> Array2<f64> {
+    let mut a = x.t().dot(x);
+    for (j, d) in diagonal.iter().enumerate() {
+        a[[j, j]] += d;
+    }
+    a
+}
+
+//solves (X'X + D) b = X'y, D all 0 is ordinary least squares
+fn least_squares(x: &Array2<f64>, y: &Array1<f64>, diagonal: &[f64]) -> Result<Array1<f64>, Box<dyn Error>> {
+    let inverse = invert(&gram(x, diagonal))?;
+    Ok(inverse.dot(&x.t().dot(y)))
+}
+
+//standard errors, t statistics, p-values and 95% confidence intervals
+//the covariance is s^2 (X'X + D)^-1 X'X (X'X + D)^-1, which is s^2 (X'X)^-1 for least squares
+//s^2 is the residual sum of squares over n - p (p counts the intercept)
+fn inference(x: &Array2<f64>, y: &Array1<f64>, beta: &Array1<f64>, diagonal: &[f64], names: Vec<String>) -> Result<Vec<Coefficient>, Box<dyn Error>> {
+    let (n, p) = x.dim();
+    if n <= p {
+        return Err(format!("need more students ({}) than coefficients ({}) for standard errors", n, p).into());
+    }
+    let residuals = y - &x.dot(beta);
+    let df = (n - p) as f64;
+    let s2 = residuals.dot(&residuals) / df;
+    let inverse = invert(&gram(x, diagonal))?;
+    let covariance = inverse.dot(&x.t().dot(x)).dot(&inverse) * s2;
+
+    let t_dist = StudentsT::new(0.0, 1.0, df)?;
+    let critical = t_dist.inverse_cdf(0.975);
+    Ok(names.into_iter().enumerate().map(|(j, name)| {
+        let estimate = beta[j];
+        let std_error = covariance[[j, j]].max(0.0).sqrt();
+        let t = estimate / std_error;
+        let p_value = 2.0 * (1.0 - t_dist.cdf(t.abs()));
+        Coefficient { name, estimate, std_error, t, p_value, ci_low: estimate - critical * std_error, ci_high: estimate + critical * std_error }
+    }).collect())
+}
+
+//inverse of a square matrix (Gauss-Jordan with partial pivoting), the matrices here are only ~25x25
+fn invert(a: &Array2<f64>) -> Result<Array2<f64>, Box<dyn Error>> {
+    let n = a.nrows();
+    let mut a = a.clone();
+    let mut inverse = Array2::<f64>::eye(n);
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[[i, col]].abs().total_cmp(&a[[j, col]].abs())).unwrap_or(col);
+        if a[[pivot, col]].abs() < 1e-10 {
+            return Err("the features are linearly dependent (a column is a mix of the others), can't invert X'X".into());
+        }
+        for k in 0..n {
+            a.swap([col, k], [pivot, k]);
+            inverse.swap([col, k], [pivot, k]);
+        }
+        let scale = a[[col, col]];
+        a.row_mut(col).mapv_inplace(|v| v / scale);
+        inverse.row_mut(col).mapv_inplace(|v| v / scale);
+        for row in 0..n {
+            if row != col && a[[row, col]] != 0.0 {
+                let factor = a[[row, col]];
+                let (a_col, inv_col) = (a.row(col).to_owned(), inverse.row(col).to_owned());
+                a.row_mut(row).scaled_add(-factor, &a_col);
+                inverse.row_mut(row).scaled_add(-factor, &inv_col);
+            }
+        }
+    }
+    Ok(inverse)
+}
+
+//coordinate descent for (1/2n) |y - Xb|^2 + lambda sum(|std_j b_j|), the intercept (column 0) isn't penalized
+//works on centered columns so the intercept comes out at the end, stops when no coefficient moves more than tolerance
+fn lasso(x: &Array2<f64>, y: &Array1<f64>, lambda: f64, max_iterations: usize, tolerance: f64) -> Array1<f64> {
+    let (n, p) = x.dim();
+    let means = x.mean_axis(Axis(0)).unwrap_or_else(|| Array1::zeros(p));
+    let y_mean = y.mean().unwrap_or(0.0);
+    let xc = x - &means;
+    let yc = y - y_mean;
+    let norms: Vec<f64> = (0..p).map(|j| xc.column(j).dot(&xc.column(j)) / n as f64).collect();
+
+    let mut beta = Array1::<f64>::zeros(p);
+    let mut residuals = yc.clone();
+    for _ in 0..max_iterations {
+        let mut biggest_move: f64 = 0.0;
+        for j in 1..p {
+            //a column that never changes can't explain anything
+            if norms[j] == 0.0 {
+                continue;
+            }
+            let column = xc.column(j);
+            let rho = column.dot(&residuals) / n as f64 + norms[j] * beta[j];
+            //norms[j] is the column's variance, so lambda * its std is the penalty on the standardized coefficient
+            let new = rho.signum() * (rho.abs() - lambda * norms[j].sqrt()).max(0.0) / norms[j];
+            if new != beta[j] {
+                residuals.scaled_add(beta[j] - new, &column);
+                biggest_move = biggest_move.max((new - beta[j]).abs());
+                beta[j] = new;
+            }
+        }
+        if biggest_move < tolerance {
+            break;
+        }
+    }
+    beta[0] = y_mean - (1..p).map(|j| means[j] * beta[j]).sum::<f64>();
+    beta
+}
@@ -4,26 +4,71 @@
 
 //This project aims to understand the connection between various characteristics and exam scores
 //This project uses the attached StudentPerformanceFactors.csv file taken from Kaggle
-//split into main.rs that runs the code and two sub trees
+//split into main.rs that runs the code and eight sub trees
+//  - data.rs that loads the csv and splits it into train/test
 //  - graph.rs that create the graph and runs all of the graph functions
+//  - features.rs that turns students into features for the models
 //  - tree.rs that runs all the DecisionTree code
+//  - linear.rs that runs the linear regressions (least squares, ridge, lasso)
+//  - neighbours.rs that predicts scores from the most similar students
+//  - metrics.rs that scores the predictions
+//  - persist.rs that saves trained models to a file and loads them back
 
 /* MODULE IMPORT */
+mod data;
+mod features;
 mod graph;
+mod linear;
+mod metrics;
+mod neighbours;
+mod persist;
 mod tree;
 #[allow(unused_imports)]
-use graph::{StudentRecord, Graph}; //marked as unused but if I remove half the code doesn't work (make it make sense)
+use graph::{StudentRecord, Graph, SimilarityFn, Level, SchoolType, PeerInfluence, EducationLevel, Distance, Gender}; //marked as unused but if I remove half the code doesn't work (make it make sense)
 
 use std::collections::HashMap; //the only crate I need that its in a module
 
+//default seed if none is given
+const SEED: u64 = 210;
+
+//HashMaps print in a different order every run, this sorts them so the output is always the same
+fn sorted<K: Ord + Clone, V: Clone>(map: &HashMap<K, V>) -> Vec<(K, V)> {
+    let mut items: Vec<(K, V)> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    items
+}
 
 fn main() {
-    /* BUILDING THE GRAPHS */
-    let mut train_graph = graph::Graph::new();
-    let mut test_graph = graph::Graph::new();
+    /* SEED */
+    //every random step uses this seed, so the same seed gives the same results (cargo run --release -- <seed> to change it)
+    let seed: u64 = std::env::args().nth(1).and_then(|x| x.parse().ok()).unwrap_or(SEED);
+    let mut rng = data::seeded_rng(seed);
+    println!("Using seed {}", seed);
+
+    /* LOADING THE DATA */
+    //blank cells in these columns (some versions of the Kaggle data have them) are kept as their own Missing category
+    let options = data::LoadOptions::default()
+        .with("teacher_quality", data::MissingPolicy::Keep)
+        .with("parental_education_level", data::MissingPolicy::Keep)
+        .with("distance_from_home", data::MissingPolicy::Keep);
+    let (data, report) = data::read_csv("StudentPerformanceFactors.csv", &options).expect("Error reading the csv");
+    println!("Read {} rows, loaded {}", report.rows, report.loaded);
+    println!("Blank cells: {:?}, skipped rows: {:?}, imputed: {:?}, kept as Missing: {:?}",
+        sorted(&report.missing), sorted(&report.skipped), sorted(&report.imputed), sorted(&report.kept));
+    for (line, error) in &report.malformed {
+        println!("Skipped line {}: {}", line, error);
+    }
+    //30% to train, 70% to test
+    //stratified so rare groups (learning disabilities, private schools) show up the same amount in both
+    let strata = data::Strata::new(vec!["learning_disabilities", "school_type"], None);
+    let split = data::split_stratified(&data, 0.3, &strata, &mut rng);
+    let train_students = data.subset(&split.train);
+    let test_students = data.subset(&split.test);
+    println!("Loaded {} students, {} to train and {} to test", data.len(), train_students.len(), test_students.len());
 
-    //30% to train_graph, 70% to test_graph
-    let _read_csv = graph::read_csv("StudentPerformanceFactors.csv", &mut train_graph, &mut test_graph, 0.3);
+    /* BUILDING THE GRAPH */
+    //only the train students need a graph, the DecisionTree uses the students directly
+    let train_graph = graph::build_graph(&data, &split.train, &graph::DefaultSimilarity);
 
     println!("Printing 1 nodes and 1 edges:");
     train_graph.print(1, 1);
@@ -33,9 +78,9 @@ fn main() {
     /* DEGREE CENTRALITY */
     let centrality: HashMap<&usize, i32> = train_graph.degree_centrality();
     println!("Degree centrality of each node:");
-    println!("{:?}", centrality);
+    println!("{:?}", sorted(&centrality));
     let mut temp: f64 = 0.0;
-    for (_, x) in &centrality { temp += *x as f64; }
+    for (_, x) in sorted(&centrality) { temp += x as f64; }
     println!("Average degree centrality is: {}\n
     There is a total of {} nodes in the graph, meaning that, on average, each node is connected to {:.2}% of all nodes",
     temp / (centrality.len() as f64), train_graph.nodes.len(), (temp / (centrality.len() as f64)) / (train_graph.nodes.len() as f64) * 100.0);
@@ -45,10 +90,8 @@ fn main() {
     /* CLUSTER NODES */
     let clusters = train_graph.clusters(3, Some(vec!["school_type", "family_income"]));
     println!("Clusters of nodes:");
-    let mut counter = 1;
-    for x in &clusters {
+    for (counter, x) in (1..).zip(&clusters) {
         println!("Cluster {} is:\n{:?}", counter, x);
-        counter += 1;
     }
     //println!("{:?}", clusters);
     println!("There are {} clusters", clusters.len());
@@ -61,32 +104,108 @@ fn main() {
 
     println!("\n\n\n\n\n\n");
 
+    /* SHORTEST PATHS */
+    //edges are similarities, so a path's length uses 1 / weight (more alike students are closer)
+    //every path based metric below uses this same edge_weight
+    let edge_weight = graph::EdgeWeight::Inverse;
+    //the chain of students linking the first two train students, and how many chains are tied with it
+    let (first, second) = (split.train[0], split.train[1]);
+    match train_graph.path(first, second, edge_weight) {
+        Some(path) => println!("Shortest path from {} to {}: {:?} with edge weights {:?} (distance {:.3})", first, second, path.nodes, path.weights, path.distance),
+        None => println!("No path from {} to {}", first, second),
+    }
+    let tied = train_graph.all_shortest_paths(first, second, edge_weight, 100);
+    println!("There are {}{} shortest paths from {} to {}", tied.len(), if tied.len() == 100 { " or more" } else { "" }, first, second);
+    //the same two students with the other ways of reading the edges (Distance is how the graph used to be read)
+    for other in [graph::EdgeWeight::Distance, graph::EdgeWeight::MaxMinus, graph::EdgeWeight::NegativeLog] {
+        if let Some(path) = train_graph.path(first, second, other) {
+            println!("With {:?} edges: {:?} with edge weights {:?} (distance {:.3})", other, path.nodes, path.weights, path.distance);
+        }
+    }
+
+    println!("\n\n\n\n\n\n");
+
+    /* BETWEENNESS CENTRALITY */
+    //the exact one needs a Dijkstra from every node (with every tied path), 100 random pivots estimate it
+    let betweenness = train_graph.approximate_betweenness(edge_weight, 100, true, &mut rng);
+    let mut bridges = sorted(&betweenness);
+    bridges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    println!("Students most often between two others (normalized betweenness from 100 pivots):");
+    for (id, value) in bridges.iter().take(10) {
+        let student = &train_graph.nodes[id];
+        println!("{}: {:.6} ({}, {} family income, {} motivation)", id, value, student.school_type, student.family_income, student.motivation_level);
+    }
+
+    println!("\n\n\n\n\n\n");
+
+    /* PAGERANK, EIGENVECTOR AND KATZ CENTRALITY */
+    //these use the edge weights as similarities, so the students with a lot of strong edges come out on top
+    let pagerank = train_graph.pagerank(&graph::PageRankParams::default());
+    let eigenvector = train_graph.eigenvector_centrality(1e-9, 1000);
+    let katz = train_graph.katz_centrality(&graph::KatzParams::default());
+    for (name, ranking) in [("PageRank", &pagerank), ("Eigenvector", &eigenvector), ("Katz", &katz)] {
+        let mut top = sorted(&ranking.scores);
+        top.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let (min, max) = (top[top.len() - 1].1, top[0].1);
+        println!("{} centrality ({} iterations, converged: {}), from {:.6} to {:.6}, top 5: {:?}",
+            name, ranking.iterations, ranking.converged, min, max, &top[..5.min(top.len())]);
+    }
+    //PageRank that always jumps back to the students with learning disabilities, who is closest to them
+    let disabilities: HashMap<usize, f64> = train_graph.nodes.iter().filter(|(_, s)| s.learning_disabilities).map(|(id, _)| (*id, 1.0)).collect();
+    let personal = train_graph.pagerank(&graph::PageRankParams { personalization: Some(disabilities), ..Default::default() });
+    let mut top = sorted(&personal.scores);
+    top.sort_by(|a, b| b.1.total_cmp(&a.1));
+    println!("PageRank personalized to the students with learning disabilities, top 5: {:?}", &top[..5.min(top.len())]);
+
+    println!("\n\n\n\n\n\n");
+
     /* CLOSENESS CENTRALITY */
-    let close_cent = train_graph.closeness_centrality();
+    //the Dijkstra from every node is the slow part, so it's done once for all three kinds of closeness
+    let distance_sums = train_graph.distance_sums(edge_weight);
+    let n = train_graph.nodes.len();
+    let close_cent: HashMap<usize, f64> = distance_sums.iter().map(|(id, sums)| (*id, sums.closeness(n, false))).collect();
     println!("Closeness centrality of each node:");
-    println!("{:?}", close_cent);
+    println!("{:?}", sorted(&close_cent));
     let mut temp: f64 = 0.0;
-    for (_, x) in &close_cent { temp += x; }
+    for (_, x) in sorted(&close_cent) { temp += x; }
     let avg = temp / (close_cent.len() as f64);
     //id, close_cent value, how much is it off by
     let mut max: (usize, f64, f64) = (0, 0.0, 0.0);
-    for (id, val) in &close_cent {
+    for (id, val) in sorted(&close_cent) {
         if (val - avg).abs() > (max.2).abs() {
-            max.0 = *id;
-            max.1 = *val;
+            max.0 = id;
+            max.1 = val;
             max.2 = val - avg;
         }
     }
     println!("Average degree centrality is: {}", avg);
     println!("Maximum difference from average is by node {}, with value {}, that is {} ({:.2}%) off the average of {}",
         max.0, max.1, max.2, ((max.1 - avg) / avg) * 100.0, avg);
+
+    //harmonic and Wasserman-Faust don't reward small components, normalized by n - 1 so they compare across graph sizes
+    let cut_off = distance_sums.values().filter(|sums| sums.reachable < n - 1).count();
+    println!("{} nodes can't reach every other node", cut_off);
+    let harmonic: HashMap<usize, f64> = distance_sums.iter().map(|(id, sums)| (*id, sums.harmonic(n, true))).collect();
+    let wasserman_faust: HashMap<usize, f64> = distance_sums.iter().map(|(id, sums)| (*id, sums.wasserman_faust(n, true))).collect();
+    for (name, scores) in [("Harmonic", &harmonic), ("Wasserman-Faust", &wasserman_faust)] {
+        let mut top = sorted(scores);
+        let avg = top.iter().map(|(_, x)| x).sum::<f64>() / top.len() as f64;
+        top.sort_by(|a, b| b.1.total_cmp(&a.1));
+        println!("{} closeness (normalized) average {:.6}, top 5: {:?}", name, avg, &top[..5.min(top.len())]);
+    }
     
     println!("\n\n\n\n\n\n");
 
 
     /* DECISION TREE */
-    let model = tree::decision_tree(&train_graph).expect("Model training error");
-    println!("Decision Tree Model:\n{:?}", model);
+    //all 19 attributes (FeatureSet::original() is the first 9 this project used)
+    let feature_set = features::FeatureSet::all();
+    let model = tree::decision_tree(&train_students, &feature_set).expect("Model training error");
+    println!("Features: {:?}", model.encoder.feature_names());
+    println!("Decision Tree Model:\n{}", model.tree.to_rules(&model.encoder));
+    //dot -Tpng decision_tree.dot -o decision_tree.png to see it
+    std::fs::write("decision_tree.dot", model.tree.to_dot(&model.encoder)).expect("Error writing decision_tree.dot");
+    println!("Decision tree written to decision_tree.dot");
 
     println!("\n\n\n\n\n\n");
 
@@ -94,12 +213,12 @@ fn main() {
     println!("Testing the model on 5 students");
     let mut counter = 0;
     let test_amount = 5;
-    for (id, student) in &test_graph.nodes {
+    for (id, student) in split.test.iter().zip(&test_students) {
         if counter < test_amount {
-            let score = tree::prediction(&model, &student);
+            let score = tree::prediction(&model, student);
             println!("\nThe predicted score for student {} with the following traits:{:?}
-            \nis {}, while their actual score is {}", id, student, score, student.exam_score);
-            println!("The predicted score is {} is off the real score ({}) by {}", score, student.exam_score, (score as i32 - student.exam_score).abs());
+            \nis {:.2}, while their actual score is {}", id, student, score, student.exam_score);
+            println!("The predicted score is {:.2} is off the real score ({}) by {:.2}", score, student.exam_score, (score - student.exam_score as f64).abs());
             counter += 1;
         }
     }
@@ -108,32 +227,137 @@ fn main() {
     let test_student: StudentRecord = StudentRecord {
         hours_studied: 23,
         attendance: 84,
-        parental_involvement: ("Low").to_string(),
-        access_to_resources: ("High").to_string(),
-        extracurricular_activities: ("No").to_string(),
+        parental_involvement: Level::Low,
+        access_to_resources: Level::High,
+        extracurricular_activities: false,
         sleep_hours: 7,
         previous_scores: 73,
-        motivation_level: ("Low").to_string(),
-        internet_access: ("Yes").to_string(),
+        motivation_level: Level::Low,
+        internet_access: true,
         tutoring_sessions: 0,
-        family_income: ("Low").to_string(),
-        teacher_quality: ("Medium").to_string(),
-        school_type: ("Public").to_string(),
-        peer_influence: ("Positive").to_string(),
+        family_income: Level::Low,
+        teacher_quality: Level::Medium,
+        school_type: SchoolType::Public,
+        peer_influence: PeerInfluence::Positive,
         physical_activity: 3,
-        learning_disabilities: ("No").to_string(),
-        parental_education_level: ("High School").to_string(),
-        distance_from_home: ("Near").to_string(),
-        gender: ("Male").to_string(),
+        learning_disabilities: false,
+        parental_education_level: EducationLevel::HighSchool,
+        distance_from_home: Distance::Near,
+        gender: Gender::Male,
         exam_score: 67, };
     let guess = tree::prediction(&model, &test_student);
-    println!("The test student has a predicted score of {}, his actual score is {}", guess, test_student.exam_score);
+    println!("The test student has a predicted score of {:.2}, his actual score is {}", guess, test_student.exam_score);
+
+    let scores = metrics::evaluate_students(&test_students, |s| tree::prediction(&model, s));
+    println!("The model on the {} test students: {:?}", scores.count, scores);
+
+    println!("\n\n\n\n\n\n");
+
+    /* RANDOM FOREST */
+    //100 trees on the same train students, averaged together
+    //saved with its encoder after training, later runs with the same seed load it back instead of training again
+    //(it has its own rng, so everything after it comes out the same whether it was loaded or trained)
+    let forest_path = format!("random_forest.{}.model.json", seed);
+    let feature_names = features::FeatureEncoder::fit(&train_students, &feature_set).feature_names();
+    let forest = match persist::load(&forest_path) {
+        Ok(persist::SavedModel::Forest(forest)) if forest.encoder.feature_names() == feature_names => {
+            println!("Loaded the random forest from {}", forest_path);
+            forest
+        }
+        saved => {
+            match saved {
+                Ok(_) => println!("{} is not a random forest on these features, training a new one", forest_path),
+                Err(e) => println!("No saved random forest ({}), training one", e),
+            }
+            let forest = tree::random_forest(&train_students, &feature_set, &tree::ForestParams::default(), &mut data::seeded_rng(seed)).expect("Random forest error");
+            persist::save(&forest_path, &persist::SavedModel::Forest(forest.clone())).expect("Error saving the model");
+            println!("Random forest saved to {}", forest_path);
+            forest
+        }
+    };
+    println!("Random forest out-of-bag: {:?}", forest.oob);
+    let forest_scores = metrics::evaluate_students(&test_students, |s| tree::prediction(&forest, s));
+    println!("Random forest on the {} test students: {:?}", forest_scores.count, forest_scores);
 
-    let accuracy = tree::accuracy(&test_graph, model.clone()) * 100.0;
-    println!("The model has an accuracy of: {:.2}%", accuracy);
+    println!("\n\n\n\n\n\n");
+
+    /* GRADIENT BOOSTING */
+    //small trees one after the other, stops early once 20% of the train students held back stop getting better
+    let boost = tree::gradient_boosting(&train_students, &feature_set, &tree::BoostParams::default(), &mut rng).expect("Gradient boosting error");
+    println!("Gradient boosting kept {} trees", boost.trees.len());
+    let boost_scores = metrics::evaluate_students(&test_students, |s| tree::prediction(&boost, s));
+    println!("Gradient boosting on the {} test students: {:?}", boost_scores.count, boost_scores);
+
+    println!("\n\n\n\n\n\n");
+
+    /* LINEAR REGRESSION */
+    //how many points each feature adds or takes away, categories are compared to the first one of their column
+    let ols = linear::linear_regression(&train_students, &feature_set, linear::Penalty::None).expect("Linear regression error");
+    println!("{:<40} {:>10} {:>10} {:>10} {:>10} {:>22}", "Coefficient", "Estimate", "Std error", "t", "p-value", "95% CI");
+    for c in &ols.report {
+        println!("{:<40} {:>10.4} {:>10.4} {:>10.3} {:>10.4} {:>10.4} to {:>8.4}", c.name, c.estimate, c.std_error, c.t, c.p_value, c.ci_low, c.ci_high);
+    }
+    for (name, penalty) in [("Least squares", linear::Penalty::None), ("Ridge (lambda 0.05)", linear::Penalty::Ridge(0.05)), ("Lasso (lambda 0.05)", linear::Penalty::Lasso(0.05))] {
+        let model = linear::linear_regression(&train_students, &feature_set, penalty).expect("Linear regression error");
+        let scores = metrics::evaluate_students(&test_students, |s| tree::prediction(&model, s));
+        let zeros = model.coefficients.iter().filter(|b| **b == 0.0).count();
+        println!("{} on the {} test students ({} coefficients at 0): {:?}", name, scores.count, zeros, scores);
+    }
+
+    println!("\n\n\n\n\n\n");
+
+    /* NEIGHBOUR PREDICTION */
+    //if similar students score alike, their scores should beat guessing the mean train score for everyone
+    let train_mean = train_students.iter().map(|s| s.exam_score as f64).sum::<f64>() / train_students.len() as f64;
+    let mean_scores = metrics::evaluate_students(&test_students, |_| train_mean);
+    println!("Mean train score ({:.2}) on the {} test students: {:?}", train_mean, mean_scores.count, mean_scores);
+    //the 10 most similar train nodes by the same weight as the edges of train_graph
+    let graph_scores = metrics::evaluate_students(&test_students, |s| neighbours::graph_prediction(&train_graph, s, &graph::DefaultSimilarity, 10));
+    println!("Graph neighbours on the {} test students: {:?}", graph_scores.count, graph_scores);
+    //the 10 closest train students over all 19 features
+    let knn = neighbours::knn(&train_students, &feature_set, 10).expect("kNN error");
+    let knn_scores = metrics::evaluate_students(&test_students, |s| tree::prediction(&knn, s));
+    println!("10 nearest neighbours on the {} test students: {:?}", knn_scores.count, knn_scores);
+
+    //one graph with the train and the test students, only the train scores are known and they spread to the test nodes
+    //if similar students score alike (homophily), the test nodes end up close to their real score
+    for (name, similarity) in [("default", &graph::DefaultSimilarity as &dyn SimilarityFn),
+        ("study habits", &graph::NumericSimilarity::new(vec![("hours_studied", 2.0), ("attendance", 3.0), ("previous_scores", 5.0)]))] {
+        let combined_graph = graph::build_graph(&data, &data.ids(), similarity);
+        let propagation = neighbours::label_propagation(&combined_graph, &split.train, &neighbours::PropagationParams::default());
+        let predicted: Vec<f64> = split.test.iter().map(|id| propagation.scores[id]).collect();
+        let actual: Vec<f64> = test_students.iter().map(|s| s.exam_score as f64).collect();
+        let propagation_scores = metrics::evaluate(&predicted, &actual, &metrics::DEFAULT_WITHIN);
+        println!("Label propagation on the {} graph ({} iterations, converged: {}) on the {} test students: {:?}",
+            name, propagation.iterations, propagation.converged, propagation_scores.count, propagation_scores);
+    }
+
+    println!("\n\n\n\n\n\n");
+
+    /* CROSS VALIDATION */
+    //5 folds over all of the data, stratified the same way as the split
+    //each model gets an rng from the same seed so they are tested on the exact same folds
+    let cv = tree::cross_validate(&data, &feature_set, 5, 1, Some(&strata), &mut data::seeded_rng(seed)).expect("Cross validation error");
+    for (counter, fold) in (1..).zip(&cv.folds) {
+        println!("Fold {}: {:?}", counter, sorted(fold));
+    }
+    println!("Decision tree mean over the folds: {:?}", sorted(&cv.mean));
+    println!("Decision tree std over the folds: {:?}", sorted(&cv.std));
+    let boost_cv = tree::cross_validate_with(&data, 5, 1, Some(&strata), &mut data::seeded_rng(seed),
+        |students, rng| tree::gradient_boosting(students, &feature_set, &tree::BoostParams::default(), rng)).expect("Cross validation error");
+    println!("Gradient boosting mean over the folds: {:?}", sorted(&boost_cv.mean));
+    println!("Gradient boosting std over the folds: {:?}", sorted(&boost_cv.std));
+    let ols_cv = tree::cross_validate_with(&data, 5, 1, Some(&strata), &mut data::seeded_rng(seed),
+        |students, _| linear::linear_regression(students, &feature_set, linear::Penalty::None)).expect("Cross validation error");
+    println!("Least squares mean over the folds: {:?}", sorted(&ols_cv.mean));
+    println!("Least squares std over the folds: {:?}", sorted(&ols_cv.std));
 
-    let importance = tree::feature_importance(&train_graph, &model.clone());
-    println!("\nFeature Importance: (as percent)\n{:?}", importance);
+    //shuffles each feature of the test students 5 times
+    let importance = tree::feature_importance(&test_students, &model, 5, &mut rng).expect("Feature importance error");
+    println!("\nFeature Importance: (increase in mean absolute error when shuffled)");
+    for (name, x) in sorted(&importance) {
+        println!("{}: {:.4} (std {:.4})", name, x.mean, x.std);
+    }
 }
 
 
@@ -156,29 +380,29 @@ mod tests {
         let mut graph = Graph::new();
         //same students each function
         let student1 = StudentRecord {
-            school_type: "Public".to_string(),
-            family_income: "High".to_string(),
-            motivation_level: "High".to_string(),
-            peer_influence: "Positive".to_string(),
-            learning_disabilities: "No".to_string(),
+            school_type: SchoolType::Public,
+            family_income: Level::High,
+            motivation_level: Level::High,
+            peer_influence: PeerInfluence::Positive,
+            learning_disabilities: false,
             ..Default::default()
         };
 
         let student2 = StudentRecord {
-            school_type: "Private".to_string(),
-            family_income: "Medium".to_string(),
-            motivation_level: "Medium".to_string(),
-            peer_influence: "Negative".to_string(),
-            learning_disabilities: "No".to_string(),
+            school_type: SchoolType::Private,
+            family_income: Level::Medium,
+            motivation_level: Level::Medium,
+            peer_influence: PeerInfluence::Negative,
+            learning_disabilities: false,
             ..Default::default()
         };
 
         let student3 = StudentRecord {
-            school_type: "Public".to_string(),
-            family_income: "Low".to_string(),
-            motivation_level: "Low".to_string(),
-            peer_influence: "Positive".to_string(),
-            learning_disabilities: "Yes".to_string(),
+            school_type: SchoolType::Public,
+            family_income: Level::Low,
+            motivation_level: Level::Low,
+            peer_influence: PeerInfluence::Positive,
+            learning_disabilities: true,
             ..Default::default()
         };
         graph.add_student(student1, 1);
@@ -201,29 +425,29 @@ mod tests {
         let mut graph = Graph::new();
 
         let student1 = StudentRecord {
-            school_type: "Public".to_string(),
-            family_income: "High".to_string(),
-            motivation_level: "High".to_string(),
-            peer_influence: "Positive".to_string(),
-            learning_disabilities: "No".to_string(),
+            school_type: SchoolType::Public,
+            family_income: Level::High,
+            motivation_level: Level::High,
+            peer_influence: PeerInfluence::Positive,
+            learning_disabilities: false,
             ..Default::default()
         };
 
         let student2 = StudentRecord {
-            school_type: "Private".to_string(),
-            family_income: "Medium".to_string(),
-            motivation_level: "Medium".to_string(),
-            peer_influence: "Negative".to_string(),
-            learning_disabilities: "No".to_string(),
+            school_type: SchoolType::Private,
+            family_income: Level::Medium,
+            motivation_level: Level::Medium,
+            peer_influence: PeerInfluence::Negative,
+            learning_disabilities: false,
             ..Default::default()
         };
 
         let student3 = StudentRecord {
-            school_type: "Public".to_string(),
-            family_income: "Low".to_string(),
-            motivation_level: "Low".to_string(),
-            peer_influence: "Positive".to_string(),
-            learning_disabilities: "Yes".to_string(),
+            school_type: SchoolType::Public,
+            family_income: Level::Low,
+            motivation_level: Level::Low,
+            peer_influence: PeerInfluence::Positive,
+            learning_disabilities: true,
             ..Default::default()
         };
         graph.add_student(student1, 1);
@@ -233,7 +457,7 @@ mod tests {
         graph.add_edge(1,2,1);
         graph.add_edge(2, 3, 1);
 
-        let closeness_cent = graph.closeness_centrality();
+        let closeness_cent = graph.closeness_centrality(graph::EdgeWeight::Distance, false);
 
         assert!(closeness_cent.get(&1).unwrap() < closeness_cent.get(&2).unwrap());
         assert_eq!(*closeness_cent.get(&3).unwrap(), 1.0/3.0);
@@ -247,29 +471,29 @@ mod tests {
 
         //same students each funct
         let student1 = StudentRecord {
-            school_type: "Public".to_string(),
-            family_income: "High".to_string(),
-            motivation_level: "High".to_string(),
-            peer_influence: "Positive".to_string(),
-            learning_disabilities: "No".to_string(),
+            school_type: SchoolType::Public,
+            family_income: Level::High,
+            motivation_level: Level::High,
+            peer_influence: PeerInfluence::Positive,
+            learning_disabilities: false,
             ..Default::default()
         };
 
         let student2 = StudentRecord {
-            school_type: "Private".to_string(),
-            family_income: "Medium".to_string(),
-            motivation_level: "Medium".to_string(),
-            peer_influence: "Negative".to_string(),
-            learning_disabilities: "No".to_string(),
+            school_type: SchoolType::Private,
+            family_income: Level::Medium,
+            motivation_level: Level::Medium,
+            peer_influence: PeerInfluence::Negative,
+            learning_disabilities: false,
             ..Default::default()
         };
 
         let student3 = StudentRecord {
-            school_type: "Public".to_string(),
-            family_income: "Low".to_string(),
-            motivation_level: "Low".to_string(),
-            peer_influence: "Positive".to_string(),
-            learning_disabilities: "Yes".to_string(),
+            school_type: SchoolType::Public,
+            family_income: Level::Low,
+            motivation_level: Level::Low,
+            peer_influence: PeerInfluence::Positive,
+            learning_disabilities: true,
             ..Default::default()
         };
         graph.add_student(student1, 1);
@@ -286,4 +510,666 @@ mod tests {
             assert_eq!(dists.get(id), Some(&expected_dists));
         }
     }
-}
\ No newline at end of file
+
+    //build_edges should give the same edges as checking every pair with calc_weight
+    #[test]
+    fn test_build_edges() {
+        let mut graph = Graph::new();
+        let mut brute = Graph::new();
+        let incomes = [Level::Low, Level::Medium, Level::High];
+        let disabilities = [true, false];
+        for id in 1..=12 {
+            let student = StudentRecord {
+                school_type: if id % 2 == 0 { SchoolType::Public } else { SchoolType::Private },
+                family_income: incomes[id % 3],
+                motivation_level: incomes[id % 4 % 3],
+                peer_influence: PeerInfluence::Neutral,
+                learning_disabilities: disabilities[id % 5 % 2],
+                ..Default::default()
+            };
+            graph.add_student(student.clone(), id);
+            brute.add_student(student, id);
+        }
+        graph::build_edges(&mut graph, &graph::DefaultSimilarity);
+        for i in 1..=12 {
+            for j in (i + 1)..=12 {
+                let weight = graph::calc_weight(&brute.nodes[&i], &brute.nodes[&j]);
+                if weight > 0 {
+                    brute.add_edge(i, j, weight);
+                }
+            }
+        }
+
+        for id in 1..=12 {
+            let mut edges = graph.adjacency_list[&id].clone();
+            let mut expected = brute.adjacency_list[&id].clone();
+            edges.sort();
+            expected.sort();
+            assert_eq!(edges, expected);
+        }
+    }
+
+    //attribute weights are added for every match, numeric attributes only need to be within the tolerance
+    #[test]
+    fn test_similarity() {
+        let student1 = StudentRecord { school_type: SchoolType::Public, gender: Gender::Male, hours_studied: 20, attendance: 80, ..Default::default() };
+        let student2 = StudentRecord { school_type: SchoolType::Public, gender: Gender::Female, hours_studied: 22, attendance: 90, ..Default::default() };
+
+        let attributes = graph::AttributeSimilarity::new(vec![("school_type", 3), ("gender", 1)]);
+        assert_eq!(attributes.weight(&student1, &student2), 3);
+        let numeric = graph::NumericSimilarity::new(vec![("hours_studied", 2.0), ("attendance", 5.0)]);
+        assert_eq!(numeric.weight(&student1, &student2), 1);
+
+        //both ways of building edges agree
+        let mut graph = Graph::new();
+        graph.add_student(student1, 1);
+        graph.add_student(student2, 2);
+        graph::build_edges(&mut graph, &numeric);
+        assert_eq!(graph.adjacency_list[&1], vec![(2, 1)]);
+    }
+
+    //ids are the csv rows and a split puts every student in exactly one group
+    #[test]
+    fn test_split() {
+        let records: Vec<StudentRecord> = (0..20).map(|x| StudentRecord { exam_score: x, ..Default::default() }).collect();
+        let data = data::StudentData { records };
+        assert_eq!(data.get(1).unwrap().exam_score, 0);
+        assert!(data.get(0).is_none());
+
+        let split = data::split(&data, 0.5, &mut data::seeded_rng(1));
+        //same seed, same split
+        assert_eq!(data::split(&data, 0.5, &mut data::seeded_rng(1)).train, split.train);
+        let mut ids = split.train.clone();
+        ids.extend(&split.test);
+        ids.sort();
+        assert_eq!(ids, data.ids());
+
+        let graph = graph::build_graph(&data, &split.train, &graph::DefaultSimilarity);
+        assert_eq!(graph.nodes.len(), split.train.len());
+    }
+
+    //exact splits have the asked size, stratified splits keep each group's share
+    #[test]
+    fn test_split_modes() {
+        let records: Vec<StudentRecord> = (0..100).map(|x| StudentRecord {
+            learning_disabilities: x % 10 == 0,
+            exam_score: 60 + x % 20,
+            ..Default::default() }).collect();
+        let data = data::StudentData { records };
+        let mut rng = data::seeded_rng(3);
+
+        let split = data::split_exact(&data, 30, &mut rng);
+        assert_eq!(split.train.len(), 30);
+        assert_eq!(split.test.len(), 70);
+
+        let strata = data::Strata::new(vec!["learning_disabilities"], None);
+        let split = data::split_stratified(&data, 0.5, &strata, &mut rng);
+        let disabled = split.train.iter().filter(|&&id| data.get(id).unwrap().learning_disabilities).count();
+        assert_eq!(disabled, 5);
+        assert_eq!(split.train.len() + split.test.len(), 100);
+    }
+
+    //every id is tested exactly once over the k folds
+    #[test]
+    fn test_k_fold() {
+        let records: Vec<StudentRecord> = (0..23).map(|x| StudentRecord { exam_score: x, ..Default::default() }).collect();
+        let data = data::StudentData { records };
+        let folds = data::k_fold(&data, &data.ids(), 5, None, &mut data::seeded_rng(4));
+        assert_eq!(folds.len(), 5);
+
+        let mut tested: Vec<usize> = folds.iter().flat_map(|f| f.test.clone()).collect();
+        tested.sort();
+        assert_eq!(tested, data.ids());
+        for fold in &folds {
+            assert!(fold.test.len() == 4 || fold.test.len() == 5);
+            assert_eq!(fold.train.len() + fold.test.len(), 23);
+        }
+    }
+
+    //repeated k-fold gives k * repeats folds, and mean/std are over every one of them
+    #[test]
+    fn test_cross_validate() {
+        let records: Vec<StudentRecord> = (0..20).map(|x| StudentRecord {
+            hours_studied: x, exam_score: 60 + x % 7, learning_disabilities: x % 4 == 0, ..Default::default()
+        }).collect();
+        let data = data::StudentData { records };
+        let features = features::FeatureSet::all();
+        let strata = data::Strata::new(vec!["learning_disabilities"], None);
+
+        let cv = tree::cross_validate(&data, &features, 4, 3, Some(&strata), &mut data::seeded_rng(8)).unwrap();
+        assert_eq!(cv.folds.len(), 12);
+        let maes: Vec<f64> = cv.folds.iter().map(|f| f["mae"]).collect();
+        let mean = maes.iter().sum::<f64>() / 12.0;
+        let std = (maes.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 12.0).sqrt();
+        assert!((cv.mean["mae"] - mean).abs() < 1e-12);
+        assert!((cv.std["mae"] - std).abs() < 1e-12);
+        assert_eq!(cv.mean.len(), cv.folds[0].len());
+
+        //a model that uses the rng while training still gets k * repeats folds
+        let boost = tree::cross_validate_with(&data, 5, 2, None, &mut data::seeded_rng(8),
+            |students, rng| tree::gradient_boosting(students, &features, &tree::BoostParams::default(), rng)).unwrap();
+        assert_eq!(boost.folds.len(), 10);
+    }
+
+    //categories load into their enums, get_attribute gives back the csv text and typos are errors
+    #[test]
+    fn test_categories() {
+        let header = "hours_studied,attendance,parental_involvement,access_to_resources,extracurricular_activities,sleep_hours,previous_scores,motivation_level,internet_access,tutoring_sessions,family_income,teacher_quality,school_type,peer_influence,physical_activity,learning_disabilities,parental_education_level,distance_from_home,gender,exam_score";
+        let good = format!("{}\n23,84,Low,High,No,7,73,Low,Yes,0,Low,Medium,Public,Positive,3,No,High School,Near,Male,67\n", header);
+        let student: StudentRecord = csv::Reader::from_reader(good.as_bytes()).deserialize().next().unwrap().unwrap();
+        assert_eq!(student.parental_education_level, EducationLevel::HighSchool);
+        assert!(student.internet_access);
+        assert_eq!(student.get_attribute("parental_education_level"), Some("High School".to_string()));
+        assert_eq!(student.get_attribute("extracurricular_activities"), Some("No".to_string()));
+
+        let typo = format!("{}\n23,84,Low,High,No,7,73,Medum,Yes,0,Low,Medium,Public,Positive,3,No,High School,Near,Male,67\n", header);
+        let result: Option<Result<StudentRecord, csv::Error>> = csv::Reader::from_reader(typo.as_bytes()).deserialize().next();
+        assert!(result.unwrap().is_err());
+    }
+
+    //each missing value policy does its thing and the report counts it
+    #[test]
+    fn test_missing_values() {
+        let csv_text = "hours_studied,attendance,parental_involvement,access_to_resources,extracurricular_activities,sleep_hours,previous_scores,motivation_level,internet_access,tutoring_sessions,family_income,teacher_quality,school_type,peer_influence,physical_activity,learning_disabilities,parental_education_level,distance_from_home,gender,exam_score
+23,84,Low,High,No,7,73,Low,Yes,0,Low,,Public,Positive,3,No,High School,Near,Male,67
+19,,Low,Medium,No,8,59,Low,Yes,2,Medium,Medium,Public,Negative,4,No,,Moderate,Female,61
+24,98,Medium,Medium,Yes,7,91,Medium,Yes,2,Medium,Medium,Public,Neutral,4,No,Postgraduate,,Male,74
+29,89,Low,Medium,Yes,8,98,Medum,Yes,1,Medium,High,Public,Negative,4,No,High School,Moderate,Male,71
+21,90,Low,Medium,Yes,8,98,Low,Yes,1,Medium,High,Public,Negative,4,No,High School,Moderate,,70
+";
+        let options = data::LoadOptions::default()
+            .with("teacher_quality", data::MissingPolicy::Keep)
+            .with("attendance", data::MissingPolicy::Median)
+            .with("gender", data::MissingPolicy::Mode)
+            .with("parental_education_level", data::MissingPolicy::Constant("College".to_string()));
+        let (data, report) = data::load(csv::Reader::from_reader(csv_text.as_bytes()), &options).unwrap();
+
+        assert_eq!(report.rows, 5);
+        assert_eq!(report.loaded, 3);
+        assert_eq!(report.kept["teacher_quality"], 1);
+        assert_eq!(report.imputed["attendance"], 1);
+        assert_eq!(report.skipped["distance_from_home"], 1);
+        assert_eq!(report.malformed.len(), 1);
+        assert_eq!(data.get(1).unwrap().teacher_quality, Level::Missing);
+        assert_eq!(data.get(2).unwrap().attendance, 90);
+        assert_eq!(data.get(2).unwrap().parental_education_level, EducationLevel::College);
+        assert_eq!(data.get(3).unwrap().gender, Gender::Male);
+
+        //Keep only works on categories
+        let bad = data::LoadOptions::default().with("attendance", data::MissingPolicy::Keep);
+        assert!(data::load(csv::Reader::from_reader(csv_text.as_bytes()), &bad).is_err());
+        //a Constant the column can't take is an error before loading, not a malformed row each time
+        let bad = data::LoadOptions::default().with("parental_education_level", data::MissingPolicy::Constant("Colege".to_string()));
+        assert!(data::load(csv::Reader::from_reader(csv_text.as_bytes()), &bad).is_err());
+        let bad = data::LoadOptions::default().with("attendance", data::MissingPolicy::Constant("High".to_string()));
+        assert!(data::load(csv::Reader::from_reader(csv_text.as_bytes()), &bad).is_err());
+
+        //"Missing" written in the csv is an unknown value, only a blank cell under Keep becomes Missing
+        let written = csv_text.replace("Low,,Public", "Low,Missing,Public");
+        let (data, report) = data::load(csv::Reader::from_reader(written.as_bytes()), &options).unwrap();
+        assert_eq!((report.loaded, report.malformed.len()), (2, 2));
+        assert!(data.records.iter().all(|s| s.teacher_quality != Level::Missing));
+    }
+
+    //feature names line up with the encoded columns and unseen categories are all zeros
+    #[test]
+    fn test_feature_encoder() {
+        let train = vec![
+            StudentRecord { school_type: SchoolType::Public, hours_studied: 10, ..Default::default() },
+            StudentRecord { school_type: SchoolType::Private, hours_studied: 20, ..Default::default() },
+        ];
+        let feature_set = features::FeatureSet::new(vec!["school_type"], vec!["family_income"], vec!["hours_studied"]);
+        let encoder = features::FeatureEncoder::fit(&train, &feature_set);
+        assert_eq!(encoder.feature_set(), feature_set);
+        assert_eq!(encoder.feature_names(), vec!["school_type=Private", "school_type=Public", "family_income", "hours_studied"]);
+        assert_eq!(encoder.encode(&train[0]), vec![0.0, 1.0, 0.0, 10.0]);
+        assert_eq!(encoder.encode_all(&train).unwrap().shape(), &[2, 4]);
+
+        let unseen = StudentRecord { school_type: SchoolType::Missing, family_income: Level::High, hours_studied: 5, ..Default::default() };
+        assert_eq!(encoder.encode(&unseen), vec![0.0, 0.0, 2.0, 5.0]);
+
+        //every attribute but exam_score
+        let all = features::FeatureSet::all();
+        assert!(!all.contains("exam_score"));
+        assert_eq!(all.categorical.len() + all.ordinal.len() + all.numeric.len(), 19);
+    }
+
+    //the regression tree predicts the mean of each side of its split
+    #[test]
+    fn test_regression_tree() {
+        let records = ndarray::Array2::from_shape_vec((6, 1), vec![1.0, 2.0, 3.0, 10.0, 11.0, 12.0]).unwrap();
+        let targets = vec![60.0, 62.0, 61.0, 80.0, 81.0, 79.0];
+        let params = tree::TreeParams { max_depth: Some(1), min_samples_split: 2, min_samples_leaf: 1, max_features: None };
+        let fitted = tree::RegressionTree::fit(&records, &targets, &params);
+
+        assert_eq!(fitted.predict_row(&[0.0]), 61.0);
+        assert_eq!(fitted.predict_row(&[20.0]), 80.0);
+        match fitted.root {
+            tree::TreeNode::Split { threshold, .. } => assert_eq!(threshold, 6.5),
+            tree::TreeNode::Leaf { .. } => panic!("should have split"),
+        }
+    }
+
+    //checks each metric on numbers worked out by hand
+    #[test]
+    fn test_metrics() {
+        let scores = metrics::evaluate(&[60.0, 72.0, 70.0, 66.0], &[60.0, 70.0, 65.0, 65.0], &[1.0, 2.0]);
+        //errors are 0, 2, 5, 1
+        assert_eq!(scores.count, 4);
+        assert_eq!(scores.mae, 2.0);
+        assert_eq!(scores.rmse, 7.5_f64.sqrt());
+        assert_eq!(scores.median_abs_error, 1.5);
+        assert_eq!(scores.max_error, 5.0);
+        assert_eq!(scores.within, vec![(1.0, 0.5), (2.0, 0.75)]);
+        //actual mean 65, total sum of squares 50, residual 30
+        assert!((scores.r2 - 0.4).abs() < 1e-12);
+
+        let perfect = metrics::evaluate(&[50.0, 70.0], &[50.0, 70.0], &[]);
+        assert_eq!(perfect.r2, 1.0);
+        assert_eq!(perfect.mape, 0.0);
+    }
+
+    //a feature the model never uses has no importance, one it depends on does
+    #[test]
+    fn test_permutation_importance() {
+        let students: Vec<StudentRecord> = (0..40).map(|x| StudentRecord {
+            hours_studied: x,
+            school_type: if x % 2 == 0 { SchoolType::Public } else { SchoolType::Private },
+            exam_score: 50 + x,
+            ..Default::default() }).collect();
+        let feature_set = features::FeatureSet::new(vec!["school_type"], vec![], vec!["hours_studied"]);
+        let encoder = features::FeatureEncoder::fit(&students, &feature_set);
+        assert_eq!(encoder.feature_groups(), vec![("school_type".to_string(), vec![0, 1]), ("hours_studied".to_string(), vec![2])]);
+
+        //"model" that only looks at hours_studied
+        let importance = metrics::permutation_importance(&students, &encoder, |row| 50.0 + row[2], 3, &mut data::seeded_rng(5)).unwrap();
+        assert_eq!(importance["school_type"].mean, 0.0);
+        assert!(importance["hours_studied"].mean > 1.0);
+    }
+
+    //a forest averages its trees and every student gets scored out-of-bag
+    #[test]
+    fn test_random_forest() {
+        let students: Vec<StudentRecord> = (0..60).map(|x| StudentRecord { hours_studied: x % 30, attendance: x, exam_score: 50 + x % 30, ..Default::default() }).collect();
+        let feature_set = features::FeatureSet::new(vec![], vec![], vec!["hours_studied", "attendance"]);
+        let params = tree::ForestParams { trees: 20, ..Default::default() };
+        let forest = tree::random_forest(&students, &feature_set, &params, &mut data::seeded_rng(6)).unwrap();
+
+        assert_eq!(forest.trees.len(), 20);
+        assert_eq!(forest.oob.count, 60);
+        let row = forest.encoder.encode(&students[10]);
+        let mean = forest.trees.iter().map(|t| t.predict_row(&row)).sum::<f64>() / 20.0;
+        assert_eq!(tree::prediction(&forest, &students[10]), mean);
+        assert!(forest.oob.mae < 5.0);
+    }
+
+    //boosting fits a simple line better than its starting guess, with both losses, and early stopping cuts trees
+    #[test]
+    fn test_gradient_boosting() {
+        let students: Vec<StudentRecord> = (0..80).map(|x| StudentRecord { hours_studied: x, exam_score: 50 + x / 4, ..Default::default() }).collect();
+        let feature_set = features::FeatureSet::new(vec![], vec![], vec!["hours_studied"]);
+
+        for loss in [tree::Loss::Squared, tree::Loss::Absolute] {
+            let params = tree::BoostParams { loss, rounds: 50, validation: 0.0, ..Default::default() };
+            let boost = tree::gradient_boosting(&students, &feature_set, &params, &mut data::seeded_rng(7)).unwrap();
+            assert_eq!(boost.trees.len(), 50);
+            let scores = metrics::evaluate_students(&students, |s| tree::prediction(&boost, s));
+            assert!(scores.mae < 1.0);
+        }
+
+        let params = tree::BoostParams { rounds: 1000, patience: 5, ..Default::default() };
+        let boost = tree::gradient_boosting(&students, &feature_set, &params, &mut data::seeded_rng(7)).unwrap();
+        assert!(boost.trees.len() < 1000);
+        assert!(boost.validation_loss.len() <= boost.trees.len() + 5);
+    }
+
+    //least squares finds the slope of a noisy line (and knows it's not 0), ridge shrinks it, a big lasso zeroes it
+    #[test]
+    fn test_linear_regression() {
+        let students: Vec<StudentRecord> = (0..100).map(|x| StudentRecord {
+            hours_studied: x,
+            school_type: if x % 2 == 0 { SchoolType::Public } else { SchoolType::Private },
+            exam_score: 50 + x / 4 + 3 * (x % 2),
+            ..Default::default()
+        }).collect();
+        let feature_set = features::FeatureSet::new(vec!["school_type"], vec![], vec!["hours_studied"]);
+
+        let ols = linear::linear_regression(&students, &feature_set, linear::Penalty::None).unwrap();
+        //school_type=Private is the reference, so only school_type=Public and hours_studied are left
+        assert_eq!(ols.report.iter().map(|c| c.name.as_str()).collect::<Vec<&str>>(), vec!["(intercept)", "school_type=Public", "hours_studied"]);
+        let hours = &ols.report[2];
+        assert!((hours.estimate - 0.25).abs() < 0.01);
+        assert!(hours.ci_low < 0.25 && 0.25 < hours.ci_high);
+        assert!(hours.p_value < 1e-6);
+        //3 points less for Public, plus the 0.25 that x / 4 rounds down less on even x
+        assert!((ols.report[1].estimate + 2.75).abs() < 0.05);
+
+        let ridge = linear::linear_regression(&students, &feature_set, linear::Penalty::Ridge(1000.0)).unwrap();
+        assert!(ridge.coefficients[0].abs() < ols.coefficients[0].abs());
+        let lasso = linear::linear_regression(&students, &feature_set, linear::Penalty::Lasso(1000.0)).unwrap();
+        assert_eq!(lasso.coefficients, vec![0.0, 0.0]);
+        assert!(lasso.report[1].p_value.is_nan());
+
+        //the penalties are on standardized coefficients, so hours in tenths give the same predictions (and 10x smaller slopes)
+        let tenths: Vec<StudentRecord> = students.iter().map(|s| StudentRecord { hours_studied: s.hours_studied * 10, ..s.clone() }).collect();
+        for penalty in [linear::Penalty::Ridge(0.5), linear::Penalty::Lasso(0.5)] {
+            let model = linear::linear_regression(&students, &feature_set, penalty).unwrap();
+            let scaled = linear::linear_regression(&tenths, &feature_set, penalty).unwrap();
+            assert!((model.coefficients[1] - 10.0 * scaled.coefficients[1]).abs() < 1e-6);
+            assert!((tree::prediction(&model, &students[7]) - tree::prediction(&scaled, &tenths[7])).abs() < 1e-6);
+        }
+    }
+
+    //neighbours in the graph and in feature space give the score of the students most like the one predicted
+    #[test]
+    fn test_neighbour_prediction() {
+        let mut graph = Graph::new();
+        let students = [(SchoolType::Public, 60), (SchoolType::Public, 70), (SchoolType::Private, 90), (SchoolType::Private, 94)];
+        for (id, (school_type, exam_score)) in students.iter().enumerate() {
+            graph.add_student(StudentRecord { school_type: *school_type, exam_score: *exam_score, ..Default::default() }, id);
+        }
+        let similarity = graph::AttributeSimilarity::new(vec![("school_type", 1)]);
+        let private = StudentRecord { school_type: SchoolType::Private, ..Default::default() };
+        assert_eq!(neighbours::graph_prediction(&graph, &private, &similarity, 1), 92.0);
+        //nobody has a weight above 0, so it's the mean of everyone
+        let nobody = graph::AttributeSimilarity::new(vec![("school_type", 0)]);
+        assert_eq!(neighbours::graph_prediction(&graph, &private, &nobody, 1), 78.5);
+
+        let train: Vec<StudentRecord> = (0..20).map(|x| StudentRecord { hours_studied: x, exam_score: 50 + x, ..Default::default() }).collect();
+        let feature_set = features::FeatureSet::new(vec![], vec![], vec!["hours_studied"]);
+        let knn = neighbours::knn(&train, &feature_set, 3).unwrap();
+        assert_eq!(tree::prediction(&knn, &StudentRecord { hours_studied: 10, ..Default::default() }), 60.0);
+        assert_eq!(tree::prediction(&knn, &StudentRecord { hours_studied: 100, ..Default::default() }), 68.0);
+    }
+
+    //a chain 1 - 2 - 3 with 1 and 3 known, 2 ends up between them (closer to the heavier edge), 4 has no edges
+    #[test]
+    fn test_label_propagation() {
+        let mut graph = Graph::new();
+        for (id, exam_score) in [(1, 60), (2, 0), (3, 90), (4, 0)] {
+            graph.add_student(StudentRecord { exam_score, ..Default::default() }, id);
+        }
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 3, 2);
+        let propagation = neighbours::label_propagation(&graph, &[1, 3], &neighbours::PropagationParams::default());
+        assert!(propagation.converged);
+        assert_eq!(propagation.scores[&1], 60.0);
+        assert!((propagation.scores[&2] - 80.0).abs() < 1e-9);
+        assert_eq!(propagation.scores[&4], 75.0);
+
+        //an unknown middle of a longer chain needs more iterations, stopping early leaves it unconverged
+        graph.add_edge(3, 4, 1);
+        let params = neighbours::PropagationParams { max_iterations: 2, tolerance: 0.0 };
+        let propagation = neighbours::label_propagation(&graph, &[1, 4], &params);
+        assert!(!propagation.converged);
+        assert_eq!(propagation.iterations, 2);
+    }
+
+    //a saved model loads back with the same predictions, a file from another format version is rejected
+    #[test]
+    fn test_persist() {
+        let students: Vec<StudentRecord> = (0..40).map(|x| StudentRecord {
+            hours_studied: x,
+            school_type: if x % 3 == 0 { SchoolType::Private } else { SchoolType::Public },
+            exam_score: 55 + x / 2 + x * 7 % 5,
+            ..Default::default()
+        }).collect();
+        let feature_set = features::FeatureSet::new(vec!["school_type"], vec![], vec!["hours_studied"]);
+        let models = vec![
+            persist::SavedModel::Tree(tree::decision_tree(&students, &feature_set).unwrap()),
+            persist::SavedModel::Linear(linear::linear_regression(&students, &feature_set, linear::Penalty::Lasso(0.1)).unwrap()),
+            //bootstrap means and out-of-bag metrics have plenty of digits to get wrong
+            persist::SavedModel::Forest(tree::random_forest(&students, &feature_set, &tree::ForestParams { trees: 30, ..Default::default() }, &mut data::seeded_rng(3)).unwrap()),
+        ];
+        for model in models {
+            let json = persist::to_json(&model).unwrap();
+            let loaded = persist::from_json(&json).unwrap();
+            for student in &students {
+                assert_eq!(tree::prediction(&loaded, student), tree::prediction(&model, student));
+            }
+            assert_eq!(tree::Predictor::encoder(&loaded).feature_names(), vec!["school_type=Private", "school_type=Public", "hours_studied"]);
+            //every f64 comes back exactly, so saving the loaded model gives the same file
+            assert_eq!(persist::to_json(&loaded).unwrap(), json);
+
+            let old = json.replacen(&format!("\"format_version\":{}", persist::FORMAT_VERSION), "\"format_version\":0", 1);
+            assert!(persist::from_json(&old).unwrap_err().to_string().contains("format version 0"));
+        }
+        assert!(persist::from_json("{\"model\": 1}").is_err());
+    }
+
+    //the rules and the DOT graph use the real feature names and categories
+    #[test]
+    fn test_tree_export() {
+        let students: Vec<StudentRecord> = (0..40).map(|x| StudentRecord {
+            school_type: if x < 20 { SchoolType::Private } else { SchoolType::Public },
+            motivation_level: if x % 2 == 0 { Level::Low } else { Level::High },
+            exam_score: if x < 20 { 60 } else { 80 },
+            ..Default::default()
+        }).collect();
+        let feature_set = features::FeatureSet::new(vec!["school_type"], vec!["motivation_level"], vec!["hours_studied"]);
+        let model = tree::decision_tree(&students, &feature_set).unwrap();
+        assert_eq!(model.tree.to_rules(&model.encoder), "if school_type is not Private (20 students, mean 80.00)\n    score = 80.00 (20 students)\nelse school_type is Private (20 students, mean 60.00)\n    score = 60.00 (20 students)\n");
+
+        let dot = model.tree.to_dot(&model.encoder);
+        assert!(dot.starts_with("digraph tree {"));
+        assert!(dot.contains("n0 [label=\"school_type is not Private\\nthreshold = 0.5\\nsamples = 40\\nvalue = 70.00\"];"));
+        assert!(dot.contains("n0 -> n1 [label=\"yes\"];") && dot.contains("n0 -> n2 [label=\"no\"];"));
+        assert!(dot.contains("n2 [label=\"samples = 20\\nvalue = 60.00\"];"));
+
+        assert_eq!(model.encoder.describe_split(2, 0.5), ("motivation_level in [Missing, Low]".to_string(), "motivation_level in [Medium, High]".to_string()));
+        assert_eq!(model.encoder.describe_split(2, -0.5).0, "motivation_level is Missing");
+        assert_eq!(model.encoder.describe_split(2, 1.5).0, "motivation_level in [Missing, Low, Medium]");
+        assert_eq!(model.encoder.describe_split(3, 12.5).1, "hours_studied > 12.5");
+    }
+
+    //paths come back with their edge weights, ties give every path, unconnected students give none
+    #[test]
+    fn test_path_reconstruction() {
+        let mut graph = Graph::new();
+        for id in 1..=6 {
+            graph.add_student(StudentRecord::default(), id);
+        }
+        //two ways from 1 to 4 of distance 3 (through 2 or through 3), a longer one through 5, 6 is alone
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 4, 2);
+        graph.add_edge(1, 3, 2);
+        graph.add_edge(3, 4, 1);
+        graph.add_edge(1, 5, 2);
+        graph.add_edge(5, 4, 2);
+
+        let path = graph.path(1, 4, graph::EdgeWeight::Distance).unwrap();
+        assert_eq!(path, graph::Path { nodes: vec![1, 2, 4], weights: vec![1, 2], distance: 3.0 });
+        assert_eq!(graph.path(1, 1, graph::EdgeWeight::Distance).unwrap().nodes, vec![1]);
+        assert!(graph.path(1, 6, graph::EdgeWeight::Distance).is_none());
+
+        let all = graph.all_shortest_paths(1, 4, graph::EdgeWeight::Distance, 10);
+        assert_eq!(all.iter().map(|p| p.nodes.clone()).collect::<Vec<Vec<usize>>>(), vec![vec![1, 2, 4], vec![1, 3, 4]]);
+        assert_eq!(all[1].weights, vec![2, 1]);
+        assert_eq!(graph.all_shortest_paths(1, 4, graph::EdgeWeight::Distance, 1).len(), 1);
+        assert!(graph.all_shortest_paths(1, 6, graph::EdgeWeight::Distance, 10).is_empty());
+        assert_eq!(graph.shortest_path(1)[&4], 3);
+    }
+
+    //a strong (similar) edge is short once the weights are read as similarities, and closeness follows the same lengths
+    #[test]
+    fn test_edge_weight() {
+        let mut graph = Graph::new();
+        for id in 1..=3 {
+            graph.add_student(StudentRecord::default(), id);
+        }
+        //1 and 3 are very alike (5), 1 - 2 - 3 is two weak links (1 each)
+        graph.add_edge(1, 3, 5);
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 3, 1);
+
+        assert_eq!(graph.path(1, 3, graph::EdgeWeight::Distance).unwrap().nodes, vec![1, 2, 3]);
+        let inverse = graph.path(1, 3, graph::EdgeWeight::Inverse).unwrap();
+        assert_eq!((inverse.nodes, inverse.weights, inverse.distance), (vec![1, 3], vec![5], 0.2));
+        //max + 1 - w: 1 for the weight 5 edge, 5 for a weight 1 edge
+        assert_eq!(graph.path(1, 3, graph::EdgeWeight::MaxMinus).unwrap().distance, 1.0);
+        let log = graph.path(1, 3, graph::EdgeWeight::NegativeLog).unwrap();
+        assert_eq!(log.nodes, vec![1, 3]);
+        assert!((graph.path(1, 2, graph::EdgeWeight::NegativeLog).unwrap().distance - 6f64.ln()).abs() < 1e-12);
+
+        //with 1 / w, 1 is 0.2 from 3 and 1.0 from 2, 2 is 1.0 from both
+        let closeness = graph.closeness_centrality(graph::EdgeWeight::Inverse, false);
+        assert!((closeness[&1] - 1.0 / 1.2).abs() < 1e-12);
+        assert!((closeness[&2] - 0.5).abs() < 1e-12);
+        //the old reading (weights as distances) is still there
+        assert_eq!(graph.closeness_centrality(graph::EdgeWeight::Distance, false)[&1], 1.0 / 3.0);
+    }
+
+    //weight 0 edges read as Distance are length 0, so 3 - 2 - 1 is a chain of zero length edges
+    //a node already settled must not take a later node as a tied predecessor (path() used to loop forever here)
+    #[test]
+    fn test_zero_length_edges() {
+        let mut graph = Graph::new();
+        for id in 1..=3 {
+            graph.add_student(StudentRecord::default(), id);
+        }
+        graph.add_edge(3, 2, 0);
+        graph.add_edge(2, 1, 0);
+        graph.add_edge(3, 1, 4);
+
+        let paths = graph.shortest_paths_from(3, graph::EdgeWeight::Distance);
+        assert_eq!(paths.predecessors[&2], vec![(3, 0)]);
+        assert_eq!(paths.predecessors[&1], vec![(2, 0)]);
+        assert_eq!(paths.order, vec![3, 2, 1]);
+        let path = graph.path(3, 1, graph::EdgeWeight::Distance).unwrap();
+        assert_eq!((path.nodes, path.distance), (vec![3, 2, 1], 0.0));
+        assert_eq!(graph.all_shortest_paths(3, 1, graph::EdgeWeight::Distance, 10).len(), 1);
+        assert_eq!(sorted(&graph.betweenness_centrality(graph::EdgeWeight::Distance, false)), vec![(1, 0.0), (2, 1.0), (3, 0.0)]);
+    }
+
+    //a path 1 - 2 - 3 - 4 plus a leaf 5 on 3, every pair going across has to go through 2 or 3
+    #[test]
+    fn test_betweenness() {
+        let mut graph = Graph::new();
+        for id in 1..=5 {
+            graph.add_student(StudentRecord::default(), id);
+        }
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 3, 1);
+        graph.add_edge(3, 4, 1);
+        graph.add_edge(3, 5, 1);
+
+        let exact = graph.betweenness_centrality(graph::EdgeWeight::Distance, false);
+        //2 is between 1 and {3, 4, 5}, 3 is between {1, 2} and {4, 5} plus 4 and 5
+        assert_eq!(sorted(&exact), vec![(1, 0.0), (2, 3.0), (3, 5.0), (4, 0.0), (5, 0.0)]);
+        let normalized = graph.betweenness_centrality(graph::EdgeWeight::Distance, true);
+        assert!((normalized[&3] - 5.0 / 6.0).abs() < 1e-12);
+
+        //two tied paths from 1 to 4 split the pair between 2 and 3
+        let mut square = Graph::new();
+        for id in 1..=4 {
+            square.add_student(StudentRecord::default(), id);
+        }
+        square.add_edge(1, 2, 1);
+        square.add_edge(2, 4, 1);
+        square.add_edge(1, 3, 1);
+        square.add_edge(3, 4, 1);
+        assert_eq!(square.betweenness_centrality(graph::EdgeWeight::Distance, false)[&2], 0.5);
+        //with similarities read as 1/w a heavy edge becomes the shortcut
+        square.add_edge(1, 4, 5);
+        assert_eq!(square.betweenness_centrality(graph::EdgeWeight::Inverse, false)[&2], 0.0);
+
+        //with max + 1 - w the weight 5 edges are length 1, 1 and 3 only meet through 2 over them
+        let mut chain = Graph::new();
+        for id in 1..=3 {
+            chain.add_student(StudentRecord::default(), id);
+        }
+        chain.add_edge(3, 2, 5);
+        chain.add_edge(2, 1, 5);
+        chain.add_edge(3, 1, 1);
+        let max_minus = chain.betweenness_centrality(graph::EdgeWeight::MaxMinus, false);
+        assert_eq!(sorted(&max_minus), vec![(1, 0.0), (2, 1.0), (3, 0.0)]);
+
+        //as many pivots as nodes is the exact value, fewer is an estimate
+        let all = graph.approximate_betweenness(graph::EdgeWeight::Distance, 5, false, &mut data::seeded_rng(1));
+        assert_eq!(sorted(&all), sorted(&exact));
+        let some = graph.approximate_betweenness(graph::EdgeWeight::Distance, 2, false, &mut data::seeded_rng(1));
+        assert_eq!(some.len(), 5);
+        assert_eq!(some[&1], 0.0);
+    }
+
+    //a star with one strong spoke, plus a node with no edges
+    #[test]
+    fn test_spectral_centrality() {
+        let mut graph = Graph::new();
+        for id in 1..=5 {
+            graph.add_student(StudentRecord::default(), id);
+        }
+        graph.add_edge(1, 2, 3);
+        graph.add_edge(1, 3, 1);
+        graph.add_edge(1, 4, 1);
+
+        let pagerank = graph.pagerank(&graph::PageRankParams::default());
+        assert!(pagerank.converged);
+        assert!((pagerank.scores.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(pagerank.scores[&1] > pagerank.scores[&2] && pagerank.scores[&2] > pagerank.scores[&3]);
+        assert_eq!(pagerank.scores[&3], pagerank.scores[&4]);
+        //every jump lands on 3, so it beats 4 (same edge) and the lone node never gets anything
+        let personal = graph.pagerank(&graph::PageRankParams { personalization: Some(HashMap::from([(3, 1.0)])), ..Default::default() });
+        assert!(personal.scores[&3] > personal.scores[&4]);
+        assert_eq!(personal.scores[&5], 0.0);
+        let short = graph.pagerank(&graph::PageRankParams { max_iterations: 2, ..Default::default() });
+        assert!(!short.converged && short.iterations == 2);
+
+        let eigenvector = graph.eigenvector_centrality(1e-12, 10000);
+        assert!(eigenvector.converged);
+        assert!(eigenvector.scores[&1] > eigenvector.scores[&2] && eigenvector.scores[&2] > eigenvector.scores[&3]);
+        assert!(eigenvector.scores[&5].abs() < 1e-6);
+        assert!((eigenvector.scores.values().map(|x| x * x).sum::<f64>() - 1.0).abs() < 1e-9);
+
+        //katz still gives the lone node its beta
+        let katz = graph.katz_centrality(&graph::KatzParams::default());
+        assert!(katz.converged);
+        assert!(katz.scores[&1] > katz.scores[&2] && katz.scores[&2] > katz.scores[&3] && katz.scores[&3] > katz.scores[&5]);
+        assert!(katz.scores[&5] > 0.0);
+        let fixed = graph.katz_centrality(&graph::KatzParams { alpha: Some(0.1), ..Default::default() });
+        //x1 = 0.1 * (3 x2 + x3 + x4) + 1, x2 = 0.3 x1 + 1, x3 = x4 = 0.1 x1 + 1 -> x1 = 1.5 / 0.89
+        let x1 = 1.5 / 0.89;
+        assert!((fixed.scores[&1] / fixed.scores[&5] - x1).abs() < 1e-6);
+    }
+
+    //a hub with 3 neighbours and a pair on their own, classic closeness puts the pair above the hub, the other two don't
+    #[test]
+    fn test_harmonic_closeness() {
+        let mut graph = Graph::new();
+        for id in 1..=6 {
+            graph.add_student(StudentRecord::default(), id);
+        }
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(1, 3, 1);
+        graph.add_edge(1, 4, 1);
+        graph.add_edge(5, 6, 1);
+        let d = graph::EdgeWeight::Distance;
+
+        let classic = graph.closeness_centrality(d, false);
+        assert!(classic[&5] > classic[&1]);
+        assert_eq!(graph.closeness_centrality(d, true)[&1], 5.0 / 3.0);
+
+        let harmonic = graph.harmonic_centrality(d, false);
+        assert_eq!(harmonic[&1], 3.0);
+        assert_eq!(harmonic[&2], 2.0);
+        assert_eq!(harmonic[&5], 1.0);
+        assert_eq!(graph.harmonic_centrality(d, true)[&1], 3.0 / 5.0);
+
+        //hub: 3 reached, sum 3 -> 3 * 3 / 3 = 3, pair: 1 reached, sum 1 -> 1
+        let wf = graph.wasserman_faust_closeness(d, false);
+        assert_eq!((wf[&1], wf[&5]), (3.0, 1.0));
+        let wf = graph.wasserman_faust_closeness(d, true);
+        assert_eq!((wf[&1], wf[&2]), (3.0 / 5.0, 9.0 / 5.0 / 5.0));
+        assert!(wf[&1] > wf[&5]);
+
+        //every edge is the heaviest one here, max + 1 - w still gives them length 1 instead of 0
+        assert_eq!(graph.harmonic_centrality(graph::EdgeWeight::MaxMinus, false)[&1], 3.0);
+        assert_eq!(graph.closeness_centrality(graph::EdgeWeight::MaxMinus, false)[&1], 1.0 / 3.0);
+        assert_eq!(graph.wasserman_faust_closeness(graph::EdgeWeight::MaxMinus, false)[&1], 3.0);
+
+        //a node with no edges is 0 everywhere
+        graph.add_student(StudentRecord::default(), 7);
+        assert_eq!(graph.harmonic_centrality(d, true)[&7], 0.0);
+        assert_eq!(graph.wasserman_faust_closeness(d, true)[&7], 0.0);
+    }
+}
@@ -0,0 +1,145 @@
+/* THIBAUT STUSSI'S DS210 FINAL PROJECT */
+/* METRICS MODULE */
+/* Thibaut Stussi | thibauts@bu.edu */
+
+
+/* METRICS MODULE */
+//This module scores predicted exam scores against the real ones
+//replaces tree::accuracy (1 - sum|error| / sum(actual)), which was always close to 1 since scores are all around 67
+//works with any predictor (a closure that gives a score for a student), on a slice of students or a whole Graph
+
+use crate::graph::{Graph, StudentRecord};
+use crate::features::FeatureEncoder;
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+//the +/- points used for the hit rates when none are given
+pub const DEFAULT_WITHIN: [f64; 3] = [1.0, 2.0, 5.0];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metrics {
+    //amount of students scored
+    pub count: usize,
+    //mean absolute error and root mean squared error (in points)
+    pub mae: f64,
+    pub rmse: f64,
+    //1 is perfect, 0 is as good as guessing the mean score every time
+    pub r2: f64,
+    //mean absolute percentage error (in percent)
+    pub mape: f64,
+    pub median_abs_error: f64,
+    pub max_error: f64,
+    //(k, share of predictions within +/- k points of the real score)
+    pub within: Vec<(f64, f64)>,
+}
+
+impl Metrics {
+    //every metric as name -> value (used to average them over cross validation folds)
+    pub fn to_map(&self) -> HashMap<String, f64> {
+        let mut map = HashMap::from([
+            ("mae".to_string(), self.mae),
+            ("rmse".to_string(), self.rmse),
+            ("r2".to_string(), self.r2),
+            ("mape".to_string(), self.mape),
+            ("median_abs_error".to_string(), self.median_abs_error),
+            ("max_error".to_string(), self.max_error),
+        ]);
+        for (k, rate) in &self.within {
+            map.insert(format!("within_{}", k), *rate);
+        }
+        map
+    }
+}
+
+//compares predicted and actual scores (same order), within is the +/- k points for the hit rates
+pub fn evaluate(predicted: &[f64], actual: &[f64], within: &[f64]) -> Metrics {
+    let n = predicted.len().min(actual.len());
+    if n == 0 {
+        return Metrics::default();
+    }
+    let errors: Vec<f64> = (0..n).map(|i| predicted[i] - actual[i]).collect();
+    let mut abs_errors: Vec<f64> = errors.iter().map(|e| e.abs()).collect();
+
+    let mae = abs_errors.iter().sum::<f64>() / n as f64;
+    let mse = errors.iter().map(|e| e * e).sum::<f64>() / n as f64;
+
+    let mean_actual = actual[..n].iter().sum::<f64>() / n as f64;
+    let total = actual[..n].iter().map(|a| (a - mean_actual).powi(2)).sum::<f64>();
+    let residual = mse * n as f64;
+    let r2 = if total > 0.0 { 1.0 - residual / total } else { 0.0 };
+
+    //a score of 0 has no percentage error so those students are left out of mape
+    let percents: Vec<f64> = (0..n).filter(|&i| actual[i] != 0.0).map(|i| (errors[i] / actual[i]).abs() * 100.0).collect();
+    let mape = if percents.is_empty() { 0.0 } else { percents.iter().sum::<f64>() / percents.len() as f64 };
+
+    abs_errors.sort_by(|a, b| a.total_cmp(b));
+    let median_abs_error = if n % 2 == 1 { abs_errors[n / 2] } else { (abs_errors[n / 2 - 1] + abs_errors[n / 2]) / 2.0 };
+    let max_error = abs_errors[n - 1];
+
+    let within = within.iter().map(|&k| (k, abs_errors.iter().filter(|&&e| e <= k).count() as f64 / n as f64)).collect();
+
+    Metrics { count: n, mae, rmse: mse.sqrt(), r2, mape, median_abs_error, max_error, within }
+}
+
+//scores any predictor on a slice of students
+pub fn evaluate_students<F: Fn(&StudentRecord) -> f64>(students: &[StudentRecord], predict: F) -> Metrics {
+    let predicted: Vec<f64> = students.iter().map(&predict).collect();
+    let actual: Vec<f64> = students.iter().map(|s| s.exam_score as f64).collect();
+    evaluate(&predicted, &actual, &DEFAULT_WITHIN)
+}
+
+//scores any predictor on every student of a graph (ex: a test graph)
+//goes in id order so the sums (and results) are the same every run
+#[allow(dead_code)] //main.rs scores the test students as a slice
+pub fn evaluate_graph<F: Fn(&StudentRecord) -> f64>(graph: &Graph, predict: F) -> Metrics {
+    let mut ids: Vec<&usize> = graph.nodes.keys().collect();
+    ids.sort();
+    let students: Vec<StudentRecord> = ids.iter().map(|id| graph.nodes[*id].clone()).collect();
+    evaluate_students(&students, predict)
+}
+
+/* PERMUTATION IMPORTANCE */
+//how much worse (in mean absolute error) a model gets when one feature is shuffled, over every repeat
+#[derive(Debug, Clone, Default)]
+pub struct Importance {
+    pub mean: f64,
+    pub std: f64,
+}
+
+//standard permutation importance for any model that predicts from an encoded row
+//the model stays the same, the students should be held out (test) students
+//for each feature group (a whole one-hot block at once) its columns are shuffled between the students,
+//and the increase in mean absolute error is the importance, done repeats times for the mean/std
+pub fn permutation_importance<F: Fn(&[f64]) -> f64>(students: &[StudentRecord], encoder: &FeatureEncoder, predict: F,
+    repeats: usize, rng: &mut impl Rng) -> Result<HashMap<String, Importance>, Box<dyn Error>> {
+    let records = encoder.encode_all(students)?;
+    let actual: Vec<f64> = students.iter().map(|s| s.exam_score as f64).collect();
+    let rows: Vec<Vec<f64>> = records.rows().into_iter().map(|r| r.to_vec()).collect();
+    let base = evaluate(&rows.iter().map(|r| predict(r)).collect::<Vec<f64>>(), &actual, &[]).mae;
+
+    let mut importance: HashMap<String, Importance> = HashMap::new();
+    for (name, columns) in encoder.feature_groups() {
+        let mut drops: Vec<f64> = Vec::new();
+        for _ in 0..repeats.max(1) {
+            let mut order: Vec<usize> = (0..rows.len()).collect();
+            order.shuffle(rng);
+            //every student gets the group's values of a random other student, the rest of the row stays
+            let predicted: Vec<f64> = rows.iter().zip(&order).map(|(row, &other)| {
+                let mut row = row.clone();
+                for &c in &columns {
+                    row[c] = rows[other][c];
+                }
+                predict(&row)
+            }).collect();
+            drops.push(evaluate(&predicted, &actual, &[]).mae - base);
+        }
+        let mean = drops.iter().sum::<f64>() / drops.len() as f64;
+        let var = drops.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / drops.len() as f64;
+        importance.insert(name, Importance { mean, std: var.sqrt() });
+    }
+    Ok(importance)
+}
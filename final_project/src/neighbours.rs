@@ -0,0 +1,167 @@
+/* THIBAUT STUSSI'S DS210 FINAL PROJECT */
+/* NEIGHBOURS MODULE */
+/* Thibaut Stussi | thibauts@bu.edu */
+
+
+/* NEIGHBOURS MODULE */
+//This module predicts a student's exam score from the training students most like them
+//the graph was only ever used for centrality and clusters, this checks if similar students actually score alike
+//three ways to find "most like them":
+//  - the same SimilarityFn as the graph's edges (graph_prediction)
+//  - k nearest neighbours on the encoded features (KnnModel, a tree::Predictor like the others)
+//  - label propagation, the known scores spread along the edges of one graph with train and test students in it
+
+use crate::graph::{Graph, SimilarityFn, StudentRecord};
+use crate::features::{FeatureEncoder, FeatureSet};
+use crate::tree::{self, Predictor};
+
+use std::collections::HashMap;
+use std::error::Error;
+use ndarray::{Array2, Axis};
+
+/* GRAPH NEIGHBOURS */
+//the weighted mean score of the k training nodes with the highest weight to the student
+//every node tied with the k-th weight is kept too, or the cut would pick between equal students by id
+//the student doesn't need to be in the graph (ex: a test student), weight 0 nodes never count
+//if no node has a weight above 0 it falls back to the mean score of the graph
+pub fn graph_prediction(graph: &Graph, student: &StudentRecord, similarity: &dyn SimilarityFn, k: usize) -> f64 {
+    let mut weights: Vec<(u32, i32)> = graph.nodes.values()
+        .map(|node| (similarity.weight(student, node), node.exam_score))
+        .filter(|(weight, _)| *weight > 0)
+        .collect();
+    if weights.is_empty() || k == 0 {
+        return mean_score(graph);
+    }
+    weights.sort_by_key(|(weight, _)| std::cmp::Reverse(*weight));
+    let cutoff = weights[k.min(weights.len()) - 1].0;
+
+    //summed in weight order, ties in exam_score order so the result is the same every run
+    let mut kept: Vec<(u32, i32)> = weights.into_iter().filter(|(weight, _)| *weight >= cutoff).collect();
+    kept.sort();
+    let total: f64 = kept.iter().map(|(weight, _)| *weight as f64).sum();
+    kept.iter().map(|(weight, score)| *weight as f64 * *score as f64).sum::<f64>() / total
+}
+
+//mean exam score of every node (0.0 for an empty graph)
+fn mean_score(graph: &Graph) -> f64 {
+    let mut scores: Vec<i32> = graph.nodes.values().map(|s| s.exam_score).collect();
+    scores.sort();
+    if scores.is_empty() { 0.0 } else { scores.iter().map(|s| *s as f64).sum::<f64>() / scores.len() as f64 }
+}
+
+/* K NEAREST NEIGHBOURS */
+//keeps every training student encoded, a prediction is the mean score of the k closest ones
+//the columns are standardized first (hours_studied and attendance would decide everything otherwise)
+#[derive(Debug, Clone)]
+pub struct KnnModel {
+    pub k: usize,
+    pub encoder: FeatureEncoder,
+    //the standardized training rows and their scores
+    pub records: Array2<f64>,
+    pub targets: Vec<f64>,
+    //mean and std of each column in the training students (std 0 columns are left as they are)
+    pub means: Vec<f64>,
+    pub stds: Vec<f64>,
+}
+
+impl Predictor for KnnModel {
+    fn encoder(&self) -> &FeatureEncoder {
+        &self.encoder
+    }
+
+    //distance ties go to the lower training row so it's the same every run
+    fn predict_row(&self, row: &[f64]) -> f64 {
+        let row: Vec<f64> = row.iter().enumerate().map(|(j, x)| (x - self.means[j]) / self.stds[j]).collect();
+        let mut distances: Vec<(f64, usize)> = self.records.rows().into_iter().enumerate()
+            .map(|(i, other)| (other.iter().zip(&row).map(|(a, b)| (a - b).powi(2)).sum::<f64>(), i))
+            .collect();
+        let k = self.k.clamp(1, distances.len());
+        distances.select_nth_unstable_by(k - 1, |a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        distances[..k].iter().map(|(_, i)| self.targets[*i]).sum::<f64>() / k as f64
+    }
+}
+
+//"trains" a k nearest neighbours model on the students, features picks the columns like for decision_tree()
+pub fn knn(students: &[StudentRecord], features: &FeatureSet, k: usize) -> Result<KnnModel, Box<dyn Error>> {
+    let (encoder, mut records, targets) = tree::encode_training(students, features)?;
+    let means: Vec<f64> = records.mean_axis(Axis(0)).map(|m| m.to_vec()).unwrap_or_default();
+    let stds: Vec<f64> = records.std_axis(Axis(0), 0.0).iter().map(|s| if *s > 0.0 { *s } else { 1.0 }).collect();
+    for (j, mut column) in records.columns_mut().into_iter().enumerate() {
+        column.mapv_inplace(|x| (x - means[j]) / stds[j]);
+    }
+    Ok(KnnModel { k, encoder, records, targets, means, stds })
+}
+
+/* LABEL PROPAGATION */
+//when to stop spreading the scores
+#[derive(Debug, Clone)]
+pub struct PropagationParams {
+    pub max_iterations: usize,
+    //stops once no score moved by more than this in an iteration
+    pub tolerance: f64,
+}
+
+impl Default for PropagationParams {
+    fn default() -> Self {
+        PropagationParams { max_iterations: 1000, tolerance: 1e-6 }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Propagation {
+    //id -> score, the labeled nodes keep their real exam_score
+    pub scores: HashMap<usize, f64>,
+    pub iterations: usize,
+    //false if it hit max_iterations first
+    pub converged: bool,
+}
+
+//semi-supervised scores: the labeled nodes (ex: the train ids) keep their exam_score,
+//every other node starts at the mean labeled score and becomes the weighted mean of its neighbours' scores each iteration
+//(all nodes move at once from the last iteration's scores, so the node order doesn't matter)
+//a node with no edges keeps the mean labeled score
+pub fn label_propagation(graph: &Graph, labeled: &[usize], params: &PropagationParams) -> Propagation {
+    let mut ids: Vec<usize> = graph.nodes.keys().cloned().collect();
+    ids.sort();
+    let index: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let mut known = vec![false; ids.len()];
+    for id in labeled {
+        if let Some(&i) = index.get(id) {
+            known[i] = true;
+        }
+    }
+    let labeled_scores: Vec<f64> = (0..ids.len()).filter(|&i| known[i]).map(|i| graph.nodes[&ids[i]].exam_score as f64).collect();
+    let start = if labeled_scores.is_empty() { 0.0 } else { labeled_scores.iter().sum::<f64>() / labeled_scores.len() as f64 };
+    let mut scores: Vec<f64> = (0..ids.len()).map(|i| if known[i] { graph.nodes[&ids[i]].exam_score as f64 } else { start }).collect();
+
+    //the edges with indexes instead of ids, only needed for the unlabeled nodes
+    let edges: Vec<Vec<(usize, f64)>> = ids.iter().enumerate().map(|(i, id)| {
+        if known[i] {
+            return Vec::new();
+        }
+        graph.adjacency_list.get(id).map(|list| list.iter().map(|(n, w)| (index[n], *w as f64)).collect()).unwrap_or_default()
+    }).collect();
+
+    let mut propagation = Propagation::default();
+    while propagation.iterations < params.max_iterations {
+        propagation.iterations += 1;
+        let mut biggest_move: f64 = 0.0;
+        let next: Vec<f64> = (0..ids.len()).map(|i| {
+            let total: f64 = edges[i].iter().map(|(_, w)| w).sum();
+            if known[i] || total == 0.0 {
+                return scores[i];
+            }
+            let new = edges[i].iter().map(|(n, w)| w * scores[*n]).sum::<f64>() / total;
+            biggest_move = biggest_move.max((new - scores[i]).abs());
+            new
+        }).collect();
+        scores = next;
+        if biggest_move < params.tolerance {
+            propagation.converged = true;
+            break;
+        }
+    }
+    propagation.scores = ids.into_iter().zip(scores).collect();
+    propagation
+}
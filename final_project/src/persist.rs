@@ -0,0 +1,89 @@
+/* THIBAUT STUSSI'S DS210 FINAL PROJECT */
+/* PERSIST MODULE */
+/* Thibaut Stussi | thibauts@bu.edu */
+
+
+/* PERSIST MODULE */
+//This module saves trained models to a JSON file and loads them back
+//a saved model has its FeatureEncoder in it, so prediction() works on the loaded model without reading the csv again
+//the file also has the feature names (to see what the model expects without loading it) and a format version
+
+use crate::features::FeatureEncoder;
+use crate::linear::LinearModel;
+use crate::tree::{BoostModel, ForestModel, Predictor, TreeModel};
+
+use std::error::Error;
+use std::fs;
+use serde::{Deserialize, Serialize};
+
+//goes up by 1 every time the saved structs change, a file with a different version is rejected by load()
+pub const FORMAT_VERSION: u32 = 1;
+
+//every model that can be saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SavedModel {
+    Tree(TreeModel),
+    Forest(ForestModel),
+    Boost(BoostModel),
+    Linear(LinearModel),
+}
+
+impl Predictor for SavedModel {
+    fn encoder(&self) -> &FeatureEncoder {
+        match self {
+            SavedModel::Tree(model) => model.encoder(),
+            SavedModel::Forest(model) => model.encoder(),
+            SavedModel::Boost(model) => model.encoder(),
+            SavedModel::Linear(model) => model.encoder(),
+        }
+    }
+
+    fn predict_row(&self, row: &[f64]) -> f64 {
+        match self {
+            SavedModel::Tree(model) => model.predict_row(row),
+            SavedModel::Forest(model) => model.predict_row(row),
+            SavedModel::Boost(model) => model.predict_row(row),
+            SavedModel::Linear(model) => model.predict_row(row),
+        }
+    }
+}
+
+//what is actually written to the file
+#[derive(Debug, Serialize, Deserialize)]
+struct ModelFile {
+    format_version: u32,
+    feature_names: Vec<String>,
+    model: SavedModel,
+}
+
+//the model as JSON text
+pub fn to_json(model: &SavedModel) -> Result<String, Box<dyn Error>> {
+    let file = ModelFile { format_version: FORMAT_VERSION, feature_names: model.encoder().feature_names(), model: model.clone() };
+    Ok(serde_json::to_string(&file)?)
+}
+
+//reads a model back from JSON text
+//the version is checked before anything else, so an old file gives a clear error instead of a missing field one
+pub fn from_json(text: &str) -> Result<SavedModel, Box<dyn Error>> {
+    let value: serde_json::Value = serde_json::from_str(text)?;
+    match value.get("format_version").and_then(|v| v.as_u64()) {
+        Some(version) if version == FORMAT_VERSION as u64 => {}
+        Some(version) => return Err(format!("model file is format version {}, this build reads version {}", version, FORMAT_VERSION).into()),
+        None => return Err("not a model file (no format_version)".into()),
+    }
+    let file: ModelFile = serde_json::from_value(value)?;
+    //the names are saved next to the encoder, if they don't line up the file was edited or is broken
+    if file.feature_names != file.model.encoder().feature_names() {
+        return Err("the feature names in the model file don't match its encoder".into());
+    }
+    Ok(file.model)
+}
+
+pub fn save(path: &str, model: &SavedModel) -> Result<(), Box<dyn Error>> {
+    fs::write(path, to_json(model)?)?;
+    Ok(())
+}
+
+pub fn load(path: &str) -> Result<SavedModel, Box<dyn Error>> {
+    from_json(&fs::read_to_string(path)?)
+}
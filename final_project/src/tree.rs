@@ -6,226 +6,563 @@
 /* TREE.RS MODULE */
 //This module runs all of the code for creating the DecisionTree model that predicts a student's exam score
 
-use crate::graph::{StudentRecord, Graph};
+use crate::graph::StudentRecord;
+use crate::data::{self, Split, StudentData, Strata};
+use crate::features::{FeatureEncoder, FeatureSet};
+use crate::metrics::{self, Importance, Metrics};
 
 use std::collections::HashMap; //main thing for the structs
 use std::error::Error;
-//decision trees
-use linfa::prelude::*;
-use linfa::dataset::Dataset;
-use linfa_trees::DecisionTree;
 use ndarray::Array2;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 
-pub fn decision_tree(graph: &Graph) -> Result<DecisionTree<f64, usize>, Box<dyn Error>> {
-    let mut features:Vec<Vec<f64>> = Vec::new();
-    let mut labels: Vec<usize> = Vec::new();
-
-    //iterates through each StudentRecord to create their feature vector w/ exam score as label
-    for (_id, student) in graph.nodes.clone() {
-        let mut feature_v = Vec::new();
-
-        //encodes categoricals using one-hot encoding
-        let school_type_encode: Vec<f64> = match student.school_type.as_str() {
-            "Public" => vec![1.0, 0.0],
-            "Private" => vec![0.0, 1.0],
-            _ => vec![0.0, 0.0], };  
-        let family_inc_encode: Vec<f64> = match student.family_income.as_str() {
-            "Low" => vec![1.0, 0.0, 0.0],
-            "Medium" => vec![0.0, 1.0, 0.0],
-            "High" => vec![0.0, 0.0, 1.0],
-            _ => vec![0.0, 0.0, 0.0], };
-        let peer_influ_encode: Vec<f64> = match student.peer_influence.as_str() {
-            "Negative" => vec![1.0, 0.0, 0.0],
-            "Neutral" => vec![0.0, 1.0, 0.0],
-            "Positive" => vec![0.0, 0.0, 1.0],
-            _ => vec![0.0, 0.0, 0.0], };
-        let motiv_encode: Vec<f64> = match student.motivation_level.as_str() {
-            "Low" => vec![1.0, 0.0, 0.0],
-            "Medium" => vec![0.0, 1.0, 0.0],
-            "High" => vec![0.0, 0.0, 1.0],
-            _ => vec![0.0, 0.0, 0.0], };
-        let learn_disabil_encode: Vec<f64> = match student.learning_disabilities.as_str() {
-            "Yes" => vec![1.0, 0.0],
-            "No" => vec![0.0, 1.0],
-            _ => vec![0.0, 0.0], };
-
-        //adds the encoded categorical variables
-        feature_v.extend(school_type_encode);
-        feature_v.extend(family_inc_encode);
-        feature_v.extend(peer_influ_encode);
-        feature_v.extend(motiv_encode);
-        feature_v.extend(learn_disabil_encode);
-        //adds the continous variables
-        feature_v.push(student.hours_studied as f64);
-        feature_v.push(student.attendance as f64);
-        feature_v.push(student.previous_scores as f64);
-        feature_v.push(student.tutoring_sessions as f64);
-
-        features.push(feature_v);
-        //makes the exam score the label
-        labels.push(student.exam_score as usize);
-    }
-    
-    let final_features: Array2<f64> = Array2::from_shape_vec((features.len(), features[0].len()), features.concat())?;
-    let final_labels: Array2<usize> = Array2::from_shape_vec((labels.len(), 1), labels.clone())?;
-
-    let dataset = Dataset::new(final_features, final_labels).with_feature_names(
-        vec!["School Type", "Family Income", "Peer Influence", "Motivation", "Learning Disabilities", "Hours Studied",
-        "Attendance", "Previous Scores", "Tutoring Sessions"]);
-    //println!("\n\n\n\nDataset records: {:?}", dataset.records().shape());
-    //println!("\n\n\n\nDataset targets: {:?}", dataset.targets().shape());
-    //this is b/c of the randomness of the read_csv making stuff not the same
-    let rows = dataset.targets.len();
-
-    //this clones the target/records and fixes a shape issues
-    //this bug took me hours to fix, ignore the insane amount of print statements here
-    let records = dataset.records().clone();
-    let targets = dataset.targets().clone().into_shape((rows,)).unwrap();
-    let dataset = Dataset::new(records, targets);
-
-    let model = DecisionTree::params().fit(&dataset)?;
-    //println!("\n\n\n\nDataset records: {:?}", dataset.records().shape());
-    //println!("\n\n\n\nDataset targets: {:?}", dataset.targets().shape());
+/* REGRESSION TREE */
+//exam_score used to be split into 60+ classes (one per score) for linfa's DecisionTree
+//this is a regression tree instead: splits make the variance of the scores on each side as small as possible
+//and each leaf predicts the mean score of the training students that end up there
+
+//how big the tree can get
+#[derive(Debug, Clone)]
+pub struct TreeParams {
+    //None lets it grow until the other limits stop it
+    pub max_depth: Option<usize>,
+    //a node needs at least this many students to be split
+    pub min_samples_split: usize,
+    //each side of a split needs at least this many students
+    pub min_samples_leaf: usize,
+    //how many random features each split gets to pick from, None is all of them (random forests use less)
+    pub max_features: Option<usize>,
+}
+
+impl Default for TreeParams {
+    fn default() -> Self {
+        TreeParams { max_depth: Some(10), min_samples_split: 10, min_samples_leaf: 5, max_features: None }
+    }
+}
+
+//a node of the tree, value is the mean score of the training students that reached it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TreeNode {
+    Leaf { value: f64, samples: usize },
+    //students with row[feature] <= threshold go left, the rest go right
+    Split { feature: usize, threshold: f64, value: f64, samples: usize, left: Box<TreeNode>, right: Box<TreeNode> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionTree {
+    pub root: TreeNode,
+}
+
+impl RegressionTree {
+    //records is one encoded student per row, targets is their exam scores
+    //the rng is only used if params.max_features is set, so without it the same data always gives the same tree
+    pub fn fit(records: &Array2<f64>, targets: &[f64], params: &TreeParams) -> Self {
+        let rows: Vec<usize> = (0..targets.len()).collect();
+        RegressionTree::fit_rows(records, targets, rows, params, &mut data::seeded_rng(0))
+    }
+
+    //fit() on only some of the rows (a row can be in there more than once, ex: a bootstrap sample)
+    pub fn fit_rows(records: &Array2<f64>, targets: &[f64], rows: Vec<usize>, params: &TreeParams, rng: &mut impl Rng) -> Self {
+        RegressionTree { root: build_node(records, targets, rows, 0, params, rng) }
+    }
+
+    //follows the splits down to a leaf
+    pub fn predict_row(&self, row: &[f64]) -> f64 {
+        let mut node = &self.root;
+        loop {
+            match node {
+                TreeNode::Leaf { value, .. } => return *value,
+                TreeNode::Split { feature, threshold, left, right, .. } => {
+                    node = if row[*feature] <= *threshold { left } else { right };
+                }
+            }
+        }
+    }
+}
+
+//grows the tree from the students in rows (recursive)
+fn build_node<R: Rng>(records: &Array2<f64>, targets: &[f64], rows: Vec<usize>, depth: usize, params: &TreeParams, rng: &mut R) -> TreeNode {
+    let samples = rows.len();
+    let value = rows.iter().map(|&r| targets[r]).sum::<f64>() / samples.max(1) as f64;
+
+    let too_deep = params.max_depth.is_some_and(|max| depth >= max);
+    if too_deep || samples < params.min_samples_split.max(2) {
+        return TreeNode::Leaf { value, samples };
+    }
+    //the features this split can use, all of them or a random few
+    let mut features: Vec<usize> = (0..records.ncols()).collect();
+    if let Some(max) = params.max_features {
+        features.shuffle(rng);
+        features.truncate(max.max(1));
+        features.sort();
+    }
+    let Some((feature, threshold)) = best_split(records, targets, &rows, &features, params) else {
+        return TreeNode::Leaf { value, samples };
+    };
+
+    let (left_rows, right_rows): (Vec<usize>, Vec<usize>) = rows.into_iter().partition(|&r| records[[r, feature]] <= threshold);
+    TreeNode::Split {
+        feature,
+        threshold,
+        value,
+        samples,
+        left: Box::new(build_node(records, targets, left_rows, depth + 1, params, rng)),
+        right: Box::new(build_node(records, targets, right_rows, depth + 1, params, rng)),
+    }
+}
+
+//finds the (feature, threshold) with the biggest drop in the sum of squared errors, None if no split helps
+//goes over the features in order and only takes strictly better splits, so it always picks the same one
+fn best_split(records: &Array2<f64>, targets: &[f64], rows: &[usize], features: &[usize], params: &TreeParams) -> Option<(usize, f64)> {
+    let n = rows.len();
+    let total_sum: f64 = rows.iter().map(|&r| targets[r]).sum();
+    let total_sq: f64 = rows.iter().map(|&r| targets[r] * targets[r]).sum();
+    let parent_sse = total_sq - total_sum * total_sum / n as f64;
+    let min_leaf = params.min_samples_leaf.max(1);
+
+    //feature, threshold, gain
+    let mut best: Option<(usize, f64, f64)> = None;
+    for &feature in features {
+        let mut sorted = rows.to_vec();
+        sorted.sort_by(|&a, &b| records[[a, feature]].total_cmp(&records[[b, feature]]));
+
+        let mut left_sum = 0.0;
+        let mut left_sq = 0.0;
+        for i in 0..(n - 1) {
+            let y = targets[sorted[i]];
+            left_sum += y;
+            left_sq += y * y;
+
+            //can only split between two different values
+            let x = records[[sorted[i], feature]];
+            let next = records[[sorted[i + 1], feature]];
+            let left_n = i + 1;
+            let right_n = n - left_n;
+            if x == next || left_n < min_leaf || right_n < min_leaf {
+                continue;
+            }
+
+            let right_sum = total_sum - left_sum;
+            let right_sq = total_sq - left_sq;
+            let sse = (left_sq - left_sum * left_sum / left_n as f64) + (right_sq - right_sum * right_sum / right_n as f64);
+            let gain = parent_sse - sse;
+            if gain > 1e-9 && best.is_none_or(|(_, _, g)| gain > g) {
+                best = Some((feature, (x + next) / 2.0, gain));
+            }
+        }
+    }
+    best.map(|(feature, threshold, _)| (feature, threshold))
+}
+
+/* EXPORTING THE TREE */
+//printing the tree with {:?} was unreadable, these write it out with the real feature names (the encoder it was trained with)
+impl RegressionTree {
+    //the tree as indented if/else rules, ex:
+    //if attendance <= 79.5 (1914 students, mean 67.23)
+    //    if hours_studied <= 19.5 (...)
+    //        score = 63.12 (120 students)
+    //    else hours_studied > 19.5 (...)
+    pub fn to_rules(&self, encoder: &FeatureEncoder) -> String {
+        let mut rules = String::new();
+        write_rules(&self.root, encoder, 0, &mut rules);
+        rules
+    }
+
+    //the tree as a Graphviz DOT graph (dot -Tpng tree.dot -o tree.png), the left branch is the "yes" one
+    pub fn to_dot(&self, encoder: &FeatureEncoder) -> String {
+        let mut dot = String::from("digraph tree {\n    node [shape=box];\n");
+        write_dot(&self.root, encoder, &mut 0, &mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn write_rules(node: &TreeNode, encoder: &FeatureEncoder, depth: usize, rules: &mut String) {
+    let indent = "    ".repeat(depth);
+    match node {
+        TreeNode::Leaf { value, samples } => {
+            rules.push_str(&format!("{}score = {:.2} ({} students)\n", indent, value, samples));
+        }
+        TreeNode::Split { feature, threshold, left, right, .. } => {
+            let (yes, no) = encoder.describe_split(*feature, *threshold);
+            let (left_value, left_samples) = left.summary();
+            let (right_value, right_samples) = right.summary();
+            rules.push_str(&format!("{}if {} ({} students, mean {:.2})\n", indent, yes, left_samples, left_value));
+            write_rules(left, encoder, depth + 1, rules);
+            rules.push_str(&format!("{}else {} ({} students, mean {:.2})\n", indent, no, right_samples, right_value));
+            write_rules(right, encoder, depth + 1, rules);
+        }
+    }
+}
+
+//writes the node and everything under it, next is the id of the next node (the nodes are numbered in the order they're written)
+fn write_dot(node: &TreeNode, encoder: &FeatureEncoder, next: &mut usize, dot: &mut String) -> usize {
+    let id = *next;
+    *next += 1;
+    match node {
+        TreeNode::Leaf { value, samples } => {
+            dot.push_str(&format!("    n{} [label=\"samples = {}\\nvalue = {:.2}\"];\n", id, samples, value));
+        }
+        TreeNode::Split { feature, threshold, value, samples, left, right } => {
+            let (yes, _) = encoder.describe_split(*feature, *threshold);
+            dot.push_str(&format!("    n{} [label=\"{}\\nthreshold = {}\\nsamples = {}\\nvalue = {:.2}\"];\n",
+                id, yes.replace('"', "\\\""), threshold, samples, value));
+            let left_id = write_dot(left, encoder, next, dot);
+            dot.push_str(&format!("    n{} -> n{} [label=\"yes\"];\n", id, left_id));
+            let right_id = write_dot(right, encoder, next, dot);
+            dot.push_str(&format!("    n{} -> n{} [label=\"no\"];\n", id, right_id));
+        }
+    }
+    id
+}
+
+impl TreeNode {
+    //(mean score, amount of students) of the node
+    fn summary(&self) -> (f64, usize) {
+        match self {
+            TreeNode::Leaf { value, samples } | TreeNode::Split { value, samples, .. } => (*value, *samples),
+        }
+    }
+}
+
+/* PREDICTION */
+//anything that predicts a score from a student encoded with its FeatureEncoder (the tree, the random forest...)
+//so they all go through the same prediction() and feature_importance()
+pub trait Predictor {
+    fn encoder(&self) -> &FeatureEncoder;
+    fn predict_row(&self, row: &[f64]) -> f64;
+}
+
+//the trained tree and the FeatureEncoder it was trained with (prediction needs both)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeModel {
+    pub tree: RegressionTree,
+    pub encoder: FeatureEncoder,
+}
+
+impl Predictor for TreeModel {
+    fn encoder(&self) -> &FeatureEncoder {
+        &self.encoder
+    }
+
+    fn predict_row(&self, row: &[f64]) -> f64 {
+        self.tree.predict_row(row)
+    }
+}
+
+//takes the students to train on (ex: StudentData::subset of the train ids), no graph needed
+//features picks the columns the model sees (ex: FeatureSet::all() for all 19)
+pub fn decision_tree(students: &[StudentRecord], features: &FeatureSet) -> Result<TreeModel, Box<dyn Error>> {
+    decision_tree_with(students, features, &TreeParams::default())
+}
+
+//decision_tree() with a different tree size
+pub fn decision_tree_with(students: &[StudentRecord], features: &FeatureSet, params: &TreeParams) -> Result<TreeModel, Box<dyn Error>> {
+    let (encoder, final_features, targets) = encode_training(students, features)?;
+    let tree = RegressionTree::fit(&final_features, &targets, params);
     //println!("{:?}", model);
-    Ok(model)
+    Ok(TreeModel { tree, encoder })
+}
+
+//the fitted encoder, the encoded students (one per row) and their exam scores
+type Training = (FeatureEncoder, Array2<f64>, Vec<f64>);
+
+//fits the encoder on the training students and gives back their feature vectors and exam scores
+pub fn encode_training(students: &[StudentRecord], features: &FeatureSet) -> Result<Training, Box<dyn Error>> {
+    if students.is_empty() {
+        return Err("can't train a model on 0 students".into());
+    }
+    //the encoder learns the categories from the training students and makes each feature vector
+    let encoder = FeatureEncoder::fit(students, features);
+    let final_features: Array2<f64> = encoder.encode_all(students)?;
+    //makes the exam score the target
+    let targets: Vec<f64> = students.iter().map(|student| student.exam_score as f64).collect();
+    Ok((encoder, final_features, targets))
 }
 
-//given a student, predict their exam score
-//self explanatory, just makes an array using re-used code and runs a model.predict on it
-pub fn prediction(model: &DecisionTree<f64, usize>, student: &StudentRecord) -> usize {
-    let mut input_features = Vec::new();
-
-    //repeat from decision_trees()
-    let school_type_encode: Vec<f64> = match student.school_type.as_str() {
-        "Public" => vec![1.0, 0.0],
-        "Private" => vec![0.0, 1.0],
-        _ => vec![0.0, 0.0], };
-    let family_inc_encode: Vec<f64> = match student.family_income.as_str() {
-        "Low" => vec![1.0, 0.0, 0.0],
-        "Medium" => vec![0.0, 1.0, 0.0],
-        "High" => vec![0.0, 0.0, 1.0],
-        _ => vec![0.0, 0.0, 0.0], };
-    let peer_influ_encode: Vec<f64> = match student.peer_influence.as_str() {
-        "Negative" => vec![1.0, 0.0, 0.0],
-        "Neutral" => vec![0.0, 1.0, 0.0],
-        "Positive" => vec![0.0, 0.0, 1.0],
-        _ => vec![0.0, 0.0, 0.0], };
-    let motiv_encode: Vec<f64> = match student.motivation_level.as_str() {
-        "Low" => vec![1.0, 0.0, 0.0],
-        "Medium" => vec![0.0, 1.0, 0.0],
-        "High" => vec![0.0, 0.0, 1.0],
-        _ => vec![0.0, 0.0, 0.0], };
-    let learn_disabil_encode: Vec<f64> = match student.learning_disabilities.as_str() {
-        "Yes" => vec![1.0, 0.0],
-        "No" => vec![0.0, 1.0],
-        _ => vec![0.0, 0.0], };
-
-    //adds the encoded categorical variables
-    input_features.extend(school_type_encode);
-    input_features.extend(family_inc_encode);
-    input_features.extend(peer_influ_encode);
-    input_features.extend(motiv_encode);
-    input_features.extend(learn_disabil_encode);
-    //adds the continous variables
-    input_features.push(student.hours_studied as f64);
-    input_features.push(student.attendance as f64);
-    input_features.push(student.previous_scores as f64);
-    input_features.push(student.tutoring_sessions as f64);
-
-    //turns the inputs into an array
-    let input_array= ndarray::Array::from_shape_vec(
-        (1, input_features.len()), input_features).expect("Input array error oop");
-    //prediction calculation from the model
-    let prediction = model.predict(&input_array);
-
-    //println!("Predicted score: {}, actual score: {}", prediction[0], student.exam_score);
-    prediction[0]
-}
-
-pub fn accuracy(graph: &Graph, model: DecisionTree<f64, usize>) -> f64 {
-    let mut off_by: f64 = 0.0;
-    let mut actual: f64 = 0.0;
-    for (_, student) in &graph.nodes {
-        let prediction = prediction(&model, student) as f64;
-        actual += student.exam_score as f64;
-        let off: f64 = student.exam_score as f64 - prediction;
-        //println!("Predicted score: {}, actual score: {}, off by: {}", prediction, student.exam_score, off.abs());
-        off_by += off.abs();
-    }
-    //computes error percentage (off_by/actual) and subtracts by 1 to get accuracy
-    return (1.0 - (off_by / actual)) as f64
+//given a student, predict their exam score (with any model)
+//self explanatory, encodes the student with the model's encoder and runs the model on it
+pub fn prediction(model: &impl Predictor, student: &StudentRecord) -> f64 {
+    let input_features = model.encoder().encode(student);
+    //println!("Predicted score: {}, actual score: {}", prediction, student.exam_score);
+    model.predict_row(&input_features)
 }
 
 //helps me better understand what is going on by how influential each section is.
-pub fn feature_importance(graph: &Graph, model: &DecisionTree<f64, usize>) -> Result<HashMap<String, f64>, Box<dyn Error>> {
-    let mut importance: HashMap<String, f64> = HashMap::new();
-
-    let names: Vec<(usize, &str)> = vec![
-        (0, "School Type"),
-        (1, "Family Income"),
-        (2, "Peer Influence"),
-        (3, "Motivation"),
-        (4, "Learning Disabilities"),
-        (5, "Hours Studied"),
-        (6, "Attendance"),
-        (7, "Previous Scores"),
-        (8, "Tutoring Sessions"), 
-        (9, "Sleep Hours"), 
-        (10, "Internet Access"), 
-        (11, "Extracurricular Activities"),
-        (12, "Access to Resources"),
-        (13, "Parental Involvement"),
-        (14, "Teacher Quality"),
-        (15, "Physical Activity"),
-        (16, "Parental Education Level"),
-        (17, "Distance from School"),
-        (18, "Gender")];
-    let model_accuracy = accuracy(graph, model.clone());
-
-    //calculates accuracy differences in existing and altered model using altered_graph()
-    for (x, name) in names {
-        let new_graph = altered_graph(graph, x);
-        let new_model = decision_tree(&new_graph);
-
-        let new_accuracy = accuracy(&new_graph, new_model?);
-        //println!("For {}, new: {}, old: {}", name, new_accuracy, model_accuracy);
-
-        let important = (model_accuracy - new_accuracy) * 100.0;
-        importance.insert(name.to_string(), important);
-
-    }
-    Ok(importance)
-}
-
-//creates a new graph with 1 variable that is different to figure out which is most important
-pub fn altered_graph(graph: &Graph, feature: usize) -> Graph {
-    let mut new_graph = graph.clone();
-
-    for student in new_graph.nodes.values_mut() {
-        match feature {
-            //sets features to a middle-ground type of variable (or my best approximation of it)
-            0 => student.school_type = String::from("Unknown"),
-            1 => student.family_income = String::from("Medium"),
-            2 => student.peer_influence = String::from("Neutral"),
-            3 => student.motivation_level = String::from("Low"),
-            4 => student.learning_disabilities = String::from("No"),
-            5 => student.hours_studied = 0,
-            6 => student.attendance = 50,
-            7 => student.previous_scores = 50,
-            8 => student.tutoring_sessions = 0,
-            9 => student.sleep_hours = 8,
-            10 => student.internet_access = String::from("Yes"),
-            11 => student.extracurricular_activities = String::from("No"),
-            12 => student.access_to_resources = String::from("Medium"),
-            13 => student.parental_involvement = String::from("Medium"),
-            14 => student.teacher_quality = String::from("Medium"),
-            15 => student.physical_activity = 0,
-            16 => student.parental_education_level = String::from("High School"),
-            17 => student.distance_from_home = String::from("Near"),
-            18 => student.gender = String::from("Male"),
-            _ => (),
-        }
-    }
-    new_graph
-}
\ No newline at end of file
+//permutation importance (see metrics::permutation_importance), students should be held out ones (ex: the test students)
+//the old version retrained on students with a feature set to a constant and scored it on those same students
+pub fn feature_importance(students: &[StudentRecord], model: &impl Predictor, repeats: usize, rng: &mut impl Rng) -> Result<HashMap<String, Importance>, Box<dyn Error>> {
+    metrics::permutation_importance(students, model.encoder(), |row| model.predict_row(row), repeats, rng)
+}
+
+/* RANDOM FOREST */
+//a lot of trees, each trained on a bootstrap sample of the students (drawn with replacement)
+//and only a random few features to choose from at each split, the prediction is the average of every tree
+#[derive(Debug, Clone)]
+pub struct ForestParams {
+    pub trees: usize,
+    //size of each tree, if tree.max_features is None each split gets a third of the features
+    pub tree: TreeParams,
+}
+
+impl Default for ForestParams {
+    fn default() -> Self {
+        ForestParams { trees: 100, tree: TreeParams { max_depth: Some(12), min_samples_split: 5, min_samples_leaf: 3, max_features: None } }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForestModel {
+    pub trees: Vec<RegressionTree>,
+    pub encoder: FeatureEncoder,
+    //out-of-bag error: each student scored by only the trees that didn't see it in training
+    pub oob: Metrics,
+}
+
+impl Predictor for ForestModel {
+    fn encoder(&self) -> &FeatureEncoder {
+        &self.encoder
+    }
+
+    //average of every tree
+    fn predict_row(&self, row: &[f64]) -> f64 {
+        self.trees.iter().map(|t| t.predict_row(row)).sum::<f64>() / self.trees.len() as f64
+    }
+}
+
+//trains a random forest on the students (bagging + random features per split)
+pub fn random_forest(students: &[StudentRecord], features: &FeatureSet, params: &ForestParams, rng: &mut impl Rng) -> Result<ForestModel, Box<dyn Error>> {
+    let (encoder, records, targets) = encode_training(students, features)?;
+    let n = targets.len();
+    let mut tree_params = params.tree.clone();
+    if tree_params.max_features.is_none() {
+        tree_params.max_features = Some((records.ncols() / 3).max(1));
+    }
+
+    let mut trees: Vec<RegressionTree> = Vec::new();
+    //sum and count of the out-of-bag predictions of each student
+    let mut oob_sum: Vec<f64> = vec![0.0; n];
+    let mut oob_count: Vec<usize> = vec![0; n];
+    for _ in 0..params.trees.max(1) {
+        let sample: Vec<usize> = (0..n).map(|_| rng.gen_range(0..n)).collect();
+        let mut in_bag = vec![false; n];
+        for &r in &sample {
+            in_bag[r] = true;
+        }
+
+        let tree = RegressionTree::fit_rows(&records, &targets, sample, &tree_params, rng);
+        for r in (0..n).filter(|&r| !in_bag[r]) {
+            oob_sum[r] += tree.predict_row(&records.row(r).to_vec());
+            oob_count[r] += 1;
+        }
+        trees.push(tree);
+    }
+
+    //only students that were left out of at least one tree have an out-of-bag prediction
+    let scored: Vec<usize> = (0..n).filter(|&r| oob_count[r] > 0).collect();
+    let predicted: Vec<f64> = scored.iter().map(|&r| oob_sum[r] / oob_count[r] as f64).collect();
+    let actual: Vec<f64> = scored.iter().map(|&r| targets[r]).collect();
+    let oob = metrics::evaluate(&predicted, &actual, &metrics::DEFAULT_WITHIN);
+
+    Ok(ForestModel { trees, encoder, oob })
+}
+
+/* GRADIENT BOOSTING */
+//small trees trained one after the other, each one on what the trees before it got wrong
+//the prediction is the starting guess plus learning_rate times every tree
+
+//what the boosting tries to make small
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Loss {
+    //(score - prediction)^2, starts from the mean
+    Squared,
+    //|score - prediction|, starts from the median, less thrown off by outliers
+    Absolute,
+}
+
+#[derive(Debug, Clone)]
+pub struct BoostParams {
+    pub loss: Loss,
+    //most trees it will train
+    pub rounds: usize,
+    //shrinkage, each tree only counts for this much (smaller needs more rounds but usually does better)
+    pub learning_rate: f64,
+    //size of each tree (max_depth is what matters, boosting wants small trees)
+    pub tree: TreeParams,
+    //share of the training students held back to decide when to stop, 0.0 turns early stopping off
+    pub validation: f64,
+    //stops after this many rounds without the validation loss getting better
+    pub patience: usize,
+}
+
+impl Default for BoostParams {
+    fn default() -> Self {
+        BoostParams {
+            loss: Loss::Squared,
+            rounds: 500,
+            learning_rate: 0.1,
+            tree: TreeParams { max_depth: Some(3), min_samples_split: 10, min_samples_leaf: 5, max_features: None },
+            validation: 0.2,
+            patience: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoostModel {
+    //the starting guess (mean or median score)
+    pub init: f64,
+    pub learning_rate: f64,
+    pub trees: Vec<RegressionTree>,
+    pub encoder: FeatureEncoder,
+    //validation loss after each round (empty without early stopping)
+    pub validation_loss: Vec<f64>,
+}
+
+impl Predictor for BoostModel {
+    fn encoder(&self) -> &FeatureEncoder {
+        &self.encoder
+    }
+
+    fn predict_row(&self, row: &[f64]) -> f64 {
+        self.init + self.learning_rate * self.trees.iter().map(|t| t.predict_row(row)).sum::<f64>()
+    }
+}
+
+//trains a gradient boosting model on the students
+//with early stopping a random part of them (params.validation) is held back and only the best amount of trees is kept
+pub fn gradient_boosting(students: &[StudentRecord], features: &FeatureSet, params: &BoostParams, rng: &mut impl Rng) -> Result<BoostModel, Box<dyn Error>> {
+    let (encoder, records, targets) = encode_training(students, features)?;
+    let mut rows: Vec<usize> = (0..targets.len()).collect();
+    let mut valid: Vec<usize> = Vec::new();
+    if params.validation > 0.0 {
+        rows.shuffle(rng);
+        let size = ((rows.len() as f64) * params.validation).round() as usize;
+        //leaves at least 1 student to train on
+        valid = rows.split_off(rows.len() - size.min(rows.len() - 1));
+        rows.sort();
+        valid.sort();
+    }
+
+    let train_targets: Vec<f64> = rows.iter().map(|&r| targets[r]).collect();
+    let init = match params.loss {
+        Loss::Squared => train_targets.iter().sum::<f64>() / train_targets.len() as f64,
+        Loss::Absolute => median(&train_targets),
+    };
+    let mut model = BoostModel { init, learning_rate: params.learning_rate, trees: Vec::new(), encoder, validation_loss: Vec::new() };
+
+    //current prediction of every student
+    let mut current: Vec<f64> = vec![init; targets.len()];
+    let mut best = (f64::MAX, 0);
+    for round in 0..params.rounds {
+        //what the next tree is trained on, the negative gradient of the loss
+        let residuals: Vec<f64> = (0..targets.len()).map(|r| match params.loss {
+            Loss::Squared => targets[r] - current[r],
+            Loss::Absolute => (targets[r] - current[r]).signum(),
+        }).collect();
+        let mut tree = RegressionTree::fit_rows(&records, &residuals, rows.clone(), &params.tree, rng);
+        //for absolute loss each leaf should move by the median of what's left, not the mean of the signs
+        if params.loss == Loss::Absolute {
+            let left_over: Vec<f64> = (0..targets.len()).map(|r| targets[r] - current[r]).collect();
+            set_leaf_values(&mut tree.root, &records, rows.clone(), &|leaf: &[usize]| {
+                median(&leaf.iter().map(|&r| left_over[r]).collect::<Vec<f64>>())
+            });
+        }
+        for (r, value) in current.iter_mut().enumerate() {
+            *value += params.learning_rate * tree.predict_row(&records.row(r).to_vec());
+        }
+        model.trees.push(tree);
+
+        if !valid.is_empty() {
+            let loss = valid.iter().map(|&r| match params.loss {
+                Loss::Squared => (targets[r] - current[r]).powi(2),
+                Loss::Absolute => (targets[r] - current[r]).abs(),
+            }).sum::<f64>() / valid.len() as f64;
+            model.validation_loss.push(loss);
+            if loss < best.0 {
+                best = (loss, round + 1);
+            }
+            else if round + 1 - best.1 >= params.patience {
+                break;
+            }
+        }
+    }
+    //keeps the trees up to the best round
+    if !valid.is_empty() {
+        model.trees.truncate(best.1);
+    }
+    Ok(model)
+}
+
+//sends the rows down the tree and sets each leaf to value(rows that reached it), leaves nobody reached stay the same
+fn set_leaf_values(node: &mut TreeNode, records: &Array2<f64>, rows: Vec<usize>, value: &dyn Fn(&[usize]) -> f64) {
+    match node {
+        TreeNode::Leaf { value: leaf, .. } => {
+            if !rows.is_empty() {
+                *leaf = value(&rows);
+            }
+        }
+        TreeNode::Split { feature, threshold, left, right, .. } => {
+            let (left_rows, right_rows): (Vec<usize>, Vec<usize>) = rows.into_iter().partition(|&r| records[[r, *feature]] <= *threshold);
+            set_leaf_values(left, records, left_rows, value);
+            set_leaf_values(right, records, right_rows, value);
+        }
+    }
+}
+
+//median of some numbers (0.0 if there are none)
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = sorted.len();
+    if n % 2 == 1 { sorted[n / 2] } else { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 }
+}
+
+/* CROSS VALIDATION */
+//the metrics of each fold (Metrics::to_map) plus the mean/std of each metric over every fold
+#[derive(Debug, Clone, Default)]
+pub struct CrossValidation {
+    pub folds: Vec<HashMap<String, f64>>,
+    pub mean: HashMap<String, f64>,
+    pub std: HashMap<String, f64>,
+}
+
+//k-fold cross validation, trains decision_tree on k-1 folds and tests on the last one, k times
+//repeats > 1 reshuffles the folds and does it again (repeated k-fold), strata makes it stratified k-fold
+pub fn cross_validate(data: &StudentData, features: &FeatureSet, k: usize, repeats: usize, strata: Option<&Strata>, rng: &mut impl Rng) -> Result<CrossValidation, Box<dyn Error>> {
+    cross_validate_with(data, k, repeats, strata, rng, |students, _| decision_tree(students, features))
+}
+
+//cross_validate() for any model, train makes a model out of the train students of a fold
+//every fold of every repeat is made before any training, so train using the rng (ex: gradient_boosting) can't change them
+//two models with rngs made from the same seed get the exact same folds, so they can be compared
+pub fn cross_validate_with<R, P, F>(data: &StudentData, k: usize, repeats: usize, strata: Option<&Strata>, rng: &mut R, mut train: F) -> Result<CrossValidation, Box<dyn Error>>
+where R: Rng, P: Predictor, F: FnMut(&[StudentRecord], &mut R) -> Result<P, Box<dyn Error>> {
+    let mut results = CrossValidation::default();
+
+    let repeated: Vec<Vec<Split>> = (0..repeats.max(1)).map(|_| data::k_fold(data, &data.ids(), k, strata, rng)).collect();
+    for folds in repeated {
+        for fold in folds {
+            let model = train(&data.subset(&fold.train), rng)?;
+            let scores = metrics::evaluate_students(&data.subset(&fold.test), |s| prediction(&model, s));
+            results.folds.push(scores.to_map());
+        }
+    }
+
+    //mean and (population) standard deviation of each metric
+    let n = results.folds.len() as f64;
+    for name in results.folds[0].keys() {
+        let values: Vec<f64> = results.folds.iter().map(|f| f[name]).collect();
+        let mean = values.iter().sum::<f64>() / n;
+        let var = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        results.mean.insert(name.clone(), mean);
+        results.std.insert(name.clone(), var.sqrt());
+    }
+    Ok(results)
+}